@@ -0,0 +1,53 @@
+use std::time::{Duration, Instant};
+
+use configparser::ini::Ini;
+
+use crate::{config_handler::get_config_value, language_handler::LanguageStruct};
+
+// How long the editor has to be idle before it autosaves, when the config doesn't say
+const DEFAULT_AUTOSAVE_DELAY: Duration = Duration::from_secs(5);
+
+// Setup the struct which holds the autosave settings, and what is needed to tell when to autosave
+pub struct AutosaveStruct {
+    pub is_on: bool,
+    // How long after the last input the file is saved
+    pub delay: Duration,
+    // Whether the file is saved when the terminal loses focus (e.g. switching to another window)
+    pub is_on_focus_lost: bool,
+    pub last_input: Instant,
+    // A hash of the lines when the file was last saved, so files that are back to how they were saved (or were only
+    // marked as modified) aren't saved again
+    pub saved_hash: u64,
+}
+
+pub fn new_autosave(saved_hash: u64) -> AutosaveStruct {
+    AutosaveStruct {
+        is_on: false,
+        delay: DEFAULT_AUTOSAVE_DELAY,
+        is_on_focus_lost: true,
+        last_input: Instant::now(),
+        saved_hash,
+    }
+}
+
+// Get the autosave settings for a file from the config
+pub fn apply_autosave_config(
+    autosave: &mut AutosaveStruct,
+    config: &Ini,
+    file_path: &String,
+    language: &LanguageStruct,
+) {
+    autosave.is_on =
+        get_config_value(config, "autosave", file_path, language).as_deref() == Some("true");
+    autosave.delay = get_config_value(config, "autosavedelay", file_path, language)
+        .and_then(|delay| delay.parse().ok())
+        .filter(|&delay| delay > 0)
+        .map_or(DEFAULT_AUTOSAVE_DELAY, Duration::from_secs);
+    autosave.is_on_focus_lost =
+        get_config_value(config, "autosavefocus", file_path, language).as_deref() != Some("false");
+}
+
+// Check whether the editor has been idle for long enough to autosave
+pub fn is_autosave_due(autosave: &AutosaveStruct) -> bool {
+    autosave.is_on && autosave.last_input.elapsed() >= autosave.delay
+}
@@ -0,0 +1,259 @@
+use ratatui::{
+    layout::{Position, Rect},
+    style::{Color, Modifier, Style},
+    text::Span,
+    Frame,
+};
+use tui_textarea::{CursorMove, TextArea};
+
+use crate::{
+    edit_handler::jump_to, file_handler::get_content_hash, language_handler::LanguageStruct,
+};
+
+// Setup the struct which keeps what is needed to find and draw the matching bracket from one draw to the next
+pub struct BracketStruct {
+    // Which characters are code, for the lines with this hash in the language with this id. It only changes when
+    // the file does, so it isn't worked out again on every draw
+    content_hash: u64,
+    language_id: String,
+    code_mask: Vec<Vec<bool>>,
+    // How many columns the input_area is scrolled sideways, see render_matching_bracket
+    scroll_left: u16,
+}
+
+pub fn new_brackets() -> BracketStruct {
+    BracketStruct {
+        content_hash: 0,
+        language_id: String::new(),
+        code_mask: Vec::new(),
+        scroll_left: 0,
+    }
+}
+
+// Work out which characters are code rather than part of a string or comment, as one list per line
+fn get_code_mask(lines: &[String], language: &LanguageStruct) -> Vec<Vec<bool>> {
+    let mut in_block_comment = false;
+    let mut in_string: Option<char> = None;
+    let mut mask = Vec::with_capacity(lines.len());
+    for line in lines {
+        let chars: Vec<char> = line.chars().collect();
+        let mut line_mask = vec![false; chars.len()];
+        let starts_with = |i: usize, token: &str| {
+            token
+                .chars()
+                .enumerate()
+                .all(|(offset, c)| chars.get(i + offset) == Some(&c))
+        };
+        let mut i = 0;
+        while i < chars.len() {
+            if in_block_comment {
+                let end = &language.block_comment.as_ref().unwrap().1;
+                if starts_with(i, end) {
+                    in_block_comment = false;
+                    i += end.chars().count();
+                    continue;
+                }
+            } else if let Some(quote) = in_string {
+                match chars[i] {
+                    '\\' => i += 1,
+                    c if c == quote => in_string = None,
+                    _ => {
+                        // Pass
+                    }
+                }
+            } else if let Some((start, _)) = language
+                .block_comment
+                .as_ref()
+                .filter(|(start, _)| starts_with(i, start))
+            {
+                in_block_comment = true;
+                i += start.chars().count();
+                continue;
+            } else if language
+                .line_comment
+                .as_ref()
+                .is_some_and(|start| starts_with(i, start))
+            {
+                break;
+            } else if language.quotes.contains(chars[i]) {
+                in_string = Some(chars[i]);
+            } else if chars[i] == '\'' && !language.quotes.is_empty() {
+                // A character like 'x' or '\n' in a language where ' isn't always a quote
+                let length = match chars.get(i + 1) {
+                    Some('\\') => chars[i + 2..]
+                        .iter()
+                        .position(|&c| c == '\'')
+                        .map(|end| end + 3),
+                    Some(_) if chars.get(i + 2) == Some(&'\'') => Some(3),
+                    _ => None,
+                };
+                if let Some(length) = length {
+                    i += length;
+                    continue;
+                }
+                line_mask[i] = true;
+            } else {
+                line_mask[i] = true;
+            }
+            i += 1;
+        }
+        // Strings in quotes other than " don't go past the end of the line
+        if in_string.is_some_and(|quote| quote != '"') {
+            in_string = None;
+        }
+        mask.push(line_mask);
+    }
+    mask
+}
+
+// Find the bracket that matches the one under the cursor, skipping brackets in strings and comments
+pub fn find_matching_bracket(
+    brackets: &mut BracketStruct,
+    lines: &[String],
+    cursor: (usize, usize),
+    language: &LanguageStruct,
+) -> Option<(usize, usize)> {
+    let (row, col) = cursor;
+    let bracket = lines[row].chars().nth(col)?;
+    let &(opener, closer) = language
+        .brackets
+        .iter()
+        .find(|&&(opener, closer)| bracket == opener || bracket == closer)?;
+    let content_hash = get_content_hash(lines);
+    if content_hash != brackets.content_hash
+        || language.id != brackets.language_id
+        || brackets.code_mask.len() != lines.len()
+    {
+        brackets.code_mask = get_code_mask(lines, language);
+        brackets.content_hash = content_hash;
+        brackets.language_id = language.id.clone();
+    }
+    let mask = &brackets.code_mask;
+    let line_chars: Vec<Vec<char>> = lines.iter().map(|line| line.chars().collect()).collect();
+    if !mask[row][col] {
+        return None;
+    }
+    let is_forward = bracket == opener;
+    let mut depth = 0;
+    // Go through the code characters from the cursor, in the direction of the match
+    let positions: Box<dyn Iterator<Item = (usize, usize)>> = match is_forward {
+        true => Box::new((row..lines.len()).flat_map(|r| {
+            let start = if r == row { col } else { 0 };
+            (start..mask[r].len()).map(move |c| (r, c))
+        })),
+        false => Box::new((0..=row).rev().flat_map(|r| {
+            let end = if r == row { col + 1 } else { mask[r].len() };
+            (0..end).rev().map(move |c| (r, c))
+        })),
+    };
+    for (r, c) in positions {
+        if !mask[r][c] {
+            continue;
+        }
+        let found = line_chars[r][c];
+        if found == bracket {
+            depth += 1;
+        } else if found == if is_forward { closer } else { opener } {
+            depth -= 1;
+            if depth == 0 {
+                return Some((r, c));
+            }
+        }
+    }
+    None
+}
+
+// Get how many columns the first col characters of a line take up on screen
+fn get_display_width(line: &str, col: usize, tab_length: u8) -> usize {
+    let tab_length = tab_length as usize;
+    let mut width = 0;
+    for c in line.chars().take(col) {
+        width += match c {
+            '\t' if tab_length > 0 => tab_length - width % tab_length,
+            _ => Span::raw(c.to_string()).width(),
+        };
+    }
+    width
+}
+
+// Get the first row the input_area shows, as of when it was last drawn. tui-textarea doesn't say, but moving the
+// cursor into the viewport from the top of the file puts it on that row
+fn get_top_row(input_area: &mut TextArea) -> usize {
+    let (row, col) = input_area.cursor();
+    input_area.move_cursor(CursorMove::Jump(0, 0));
+    input_area.move_cursor(CursorMove::InViewport);
+    let top_row = input_area.cursor().0;
+    jump_to(input_area, row, col);
+    top_row
+}
+
+// Work out where tui-textarea scrolls to, given where it was scrolled to and where the cursor is
+fn next_scroll_top(top: u16, cursor: u16, length: u16) -> u16 {
+    match cursor {
+        cursor if cursor < top => cursor,
+        cursor if top + length <= cursor => cursor + 1 - length,
+        _ => top,
+    }
+}
+
+// Highlight the bracket matching the one under the cursor, drawn over the input_area after it has been rendered.
+// The match is placed using the input_area's first row, and how far it is scrolled sideways, which is worked out the
+// same way tui-textarea does when it draws (it only scrolls sideways when drawn, to keep the cursor on screen)
+pub fn render_matching_bracket(
+    frame: &mut Frame,
+    area: Rect,
+    input_area: &mut TextArea,
+    brackets: &mut BracketStruct,
+    language: &LanguageStruct,
+) {
+    // The text is drawn inside the input_area's border
+    let inner = Rect {
+        x: area.x + 1,
+        y: area.y + 1,
+        width: area.width.saturating_sub(2),
+        height: area.height.saturating_sub(2),
+    };
+    let cursor = input_area.cursor();
+    // The line numbers take up their digits and a space either side. tui-textarea scrolls as if the cursor were
+    // further right by that much, or twice as far right when it is that close to the start of the line
+    let gutter_width = match input_area.line_number_style() {
+        Some(_) => input_area.lines().len().to_string().len() + 2,
+        None => 0,
+    };
+    let cursor_col = match cursor.1 {
+        col if gutter_width == 0 => col,
+        col if col <= gutter_width => col * 2,
+        col => col + gutter_width,
+    };
+    brackets.scroll_left = next_scroll_top(
+        brackets.scroll_left,
+        u16::try_from(cursor_col).unwrap_or(u16::MAX),
+        inner.width,
+    );
+    let (row, col) = match find_matching_bracket(brackets, input_area.lines(), cursor, language) {
+        Some(position) => position,
+        None => return,
+    };
+    let top_row = get_top_row(input_area);
+    let x = inner.x as i64
+        + gutter_width as i64
+        + get_display_width(&input_area.lines()[row], col, input_area.tab_length()) as i64
+        - brackets.scroll_left as i64;
+    let y = inner.y as i64 + row as i64 - top_row as i64;
+    // Leave out matches that are scrolled off screen
+    let (x, y) = match (u16::try_from(x), u16::try_from(y)) {
+        (Ok(x), Ok(y)) if inner.contains(Position { x, y }) => (x, y),
+        _ => return,
+    };
+    let buffer = frame.buffer_mut();
+    if buffer.area.contains(Position { x, y }) {
+        buffer[(x, y)].set_style(get_match_style());
+    }
+}
+
+// Get the style the matching bracket is drawn with
+pub fn get_match_style() -> Style {
+    Style::default()
+        .bg(Color::DarkGray)
+        .add_modifier(Modifier::BOLD)
+}
@@ -26,12 +26,11 @@ pub fn boot_arg() {
 // Get cli argument(s) and return the file path if it is found
 pub fn get_file_path() -> String {
     let args: Vec<String> = args().collect();
-    let input_path = args[1].to_string();
+    let (input_path, _) = split_file_position(&args[1]);
     match fs::exists(&input_path).unwrap() {
         true => {
             let full_input_path = std::path::absolute(&input_path).unwrap();
-            return full_input_path.into_os_string().into_string().unwrap();
-            // return input_path_global;
+            full_input_path.into_os_string().into_string().unwrap()
         }
         false => {
             println!("Couldn't find the file! Try using -h OR --help");
@@ -40,11 +39,38 @@ pub fn get_file_path() -> String {
     }
 }
 
+// Get the position to open the file at, if one was added to the end of the file path (e.g. file.rs:120:5)
+pub fn get_start_position() -> Option<String> {
+    let args: Vec<String> = args().collect();
+    split_file_position(&args[1]).1
+}
+
+// Split a "path:line" or "path:line:col" argument into the path and the position
+// The argument is left alone if it is the path of an existing file
+fn split_file_position(arg: &str) -> (String, Option<String>) {
+    if fs::exists(arg).unwrap_or(false) {
+        return (arg.to_string(), None);
+    }
+    let is_number = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit());
+    let parts: Vec<&str> = arg.rsplitn(3, ':').collect();
+    match parts.as_slice() {
+        [col, line, path] if is_number(line) && is_number(col) => {
+            (path.to_string(), Some(format!("{line}:{col}")))
+        }
+        [line, ..] if is_number(line) => {
+            let path = &arg[..arg.len() - line.len() - 1];
+            (path.to_string(), Some(line.to_string()))
+        }
+        _ => (arg.to_string(), None),
+    }
+}
+
 // Shows the help message
 fn show_help() {
     println!("------------------------------------------------------------------------");
     println!("To open a file in Recoilless Editor, you can simply add the name or path to the file as the first argument");
     println!("This works from the current working directory or the absoloute path");
+    println!("To open the file at a line (and column), add them to the end of the path like this: file.rs:120 OR file.rs:120:5");
     println!("");
     println!("When running with cargo, you can add arguments like this:");
     println!("cargo run -- example_arg");
@@ -84,6 +110,8 @@ fn show_keybinds() {
     println!("- Alt + p: Jump back by paragraph");
     println!("- Ctrl + j: Jump to start of file");
     println!("- Alt + j: Jump to end of file");
+    println!("- %: Jump to the matching bracket");
+    println!("- Ctrl + g: Go to a line, line:col, +N/-N lines, N% of the file or bN byte offset");
     println!("");
     println!("Editing (Overview Mode)");
     println!("- Ctrl + Alt + c: Delete character");
@@ -94,8 +122,54 @@ fn show_keybinds() {
     println!("- Alt + n : Make a new line above current line");
     println!("- u: Undo");
     println!("- r: Redo");
+    println!("- Alt + u/Alt + r: Go to the previous/next state in time, across undo tree branches");
+    println!(
+        "- Ctrl + u: Open the undo tree popup (j/k to preview, Enter to go to, Esc to cancel)"
+    );
+    println!("- Ctrl + Alt + u: Go to the file as it was a number of minutes ago");
     println!("- p: Paste");
+    println!(
+        "- >/<: Indent/dedent the current line, count lines or the selected lines by one level"
+    );
+    println!("- Ctrl + f: Format the file with the formatter from the config");
+    println!("- =: Re-indent the file to the indentation style it mostly uses");
+    println!("- #/Alt + #: Toggle line/block comments on the current line, count lines or the selected lines");
+    println!("- Alt + q: Reflow the current paragraph or the selected lines to the text width");
+    println!("- .: Repeat the last change");
     println!("");
+    println!("Operators (Overview Mode)");
+    println!(
+        "- d/c/y + motion: Delete, change or yank to where the motion ends (e.g. dw, c$, y}})"
+    );
+    println!("- d/c/y + i/a + text object: Delete, change or yank inside/around a text object (e.g. diw, ca\", yit)");
+    println!("- dd, cc, yy: Delete, change or yank the whole line");
+    println!("- Motions: w b e h j k l 0 ^ $ {{ }} g G, and the Ctrl/Alt movement keys");
+    println!(
+        "- Text objects: w (word), p (paragraph), ( [ {{ < (brackets), \" ' ` (quotes), t (tag)"
+    );
+    println!();
+    println!("Marks and Jump List (Overview Mode)");
+    println!("- m + letter: Set a mark at the cursor");
+    println!("- ' + letter: Jump to a mark ('. for the last edit, '' for before the last jump)");
+    println!("- Alt + o: Go back through the jump list");
+    println!("- Ctrl + o: Go forward through the jump list");
+    println!();
+    println!("Counts (Overview Mode)");
+    println!("- number + command: Repeat a movement or editing command that many times (e.g. 5j)");
+    println!();
+    println!("Macros (Overview Mode)");
+    println!("- q + letter: Start recording a macro into the register for that letter");
+    println!("- q: Stop recording the current macro");
+    println!("- @ + letter: Replay the macro in the register for that letter");
+    println!("- @@: Replay the last replayed macro");
+    println!("- number + @ + letter: Replay the macro that many times (e.g. 5@a)");
+    println!();
+    println!("Commands (Overview Mode)");
+    println!("- : + command + Enter: Run a command");
+    println!("- :reload-config: Apply the configuration file again");
+    println!("- :wrap: Turn soft wrap on or off");
+    println!("- :messages: Open the message log");
+    println!();
     println!("- Editor (Overview Mode)");
     println!("- End: Exit program");
     println!("- Ctrl + Alt + Backspace: Exit program");
@@ -0,0 +1,181 @@
+use tui_textarea::TextArea;
+
+use crate::{
+    edit_handler::{jump_to, replace_line},
+    indent_handler::get_line_indent,
+    language_handler::LanguageStruct,
+};
+
+// Get the tokens a single line is commented with: the line comment, or the block comment around the line for
+// languages that only have block comments
+fn get_line_tokens(language: &LanguageStruct) -> Option<(&str, &str)> {
+    match (&language.line_comment, &language.block_comment) {
+        (Some(line_comment), _) => Some((line_comment, "")),
+        (None, Some((start, end))) => Some((start, end)),
+        (None, None) => None,
+    }
+}
+
+// Take a comment's start and end tokens (and the space after/before them) off some text, if it has them
+fn strip_comment(text: &str, start: &str, end: &str) -> Option<String> {
+    let text = text.strip_prefix(start)?;
+    let text = text.strip_suffix(end)?;
+    let text = text.strip_prefix(' ').unwrap_or(text);
+    let text = match end.is_empty() {
+        true => text,
+        false => text.strip_suffix(' ').unwrap_or(text),
+    };
+    Some(text.to_string())
+}
+
+// Comment out a range of rows one line at a time, or uncomment them if every line with text on it is already
+// commented. Comments go after the smallest indentation in the range, so they line up. Returns false if the
+// language has no comments
+pub fn toggle_line_comments(
+    input_area: &mut TextArea,
+    start_row: usize,
+    end_row: usize,
+    language: &LanguageStruct,
+) -> bool {
+    let (start, end) = match get_line_tokens(language) {
+        Some(tokens) => tokens,
+        None => return false,
+    };
+    let (row, col) = input_area.cursor();
+    let rows: Vec<usize> = (start_row..=end_row)
+        .filter(|&row| !input_area.lines()[row].trim().is_empty())
+        .collect();
+    let is_commented = rows
+        .iter()
+        .all(|&row| strip_comment(input_area.lines()[row].trim(), start, end.trim()).is_some());
+    // The indentation is counted in characters, as it can have whitespace that is more than one byte
+    let indent = rows
+        .iter()
+        .map(|&row| get_line_indent(&input_area.lines()[row]).chars().count())
+        .min()
+        .unwrap_or(0);
+    for &row in &rows {
+        let line = input_area.lines()[row].clone();
+        let new_line = match is_commented {
+            true => {
+                let line_indent = get_line_indent(&line);
+                match strip_comment(line.trim(), start, end.trim()) {
+                    Some(text) => format!("{line_indent}{text}"),
+                    None => line.clone(),
+                }
+            }
+            false => {
+                let end = match end.is_empty() {
+                    true => "".to_string(),
+                    false => format!(" {end}"),
+                };
+                let split = line
+                    .char_indices()
+                    .nth(indent)
+                    .map_or(line.len(), |(split, _)| split);
+                format!("{}{start} {}{end}", &line[..split], &line[split..])
+            }
+        };
+        replace_line(input_area, row, &new_line);
+    }
+    jump_to(input_area, row, col);
+    true
+}
+
+// Wrap a range of rows in a single block comment, or unwrap them if the range already starts and ends with one
+// Languages without block comments comment each line instead. Returns false if the language has no comments
+pub fn toggle_block_comment(
+    input_area: &mut TextArea,
+    start_row: usize,
+    end_row: usize,
+    language: &LanguageStruct,
+) -> bool {
+    let (start, end) = match &language.block_comment {
+        Some((start, end)) => (start.as_str(), end.as_str()),
+        None => return toggle_line_comments(input_area, start_row, end_row, language),
+    };
+    let (row, col) = input_area.cursor();
+    let first = input_area.lines()[start_row].clone();
+    let last = input_area.lines()[end_row].clone();
+    let first_indent = get_line_indent(&first);
+    // The range is one comment if the comment opened on the first row is the one that closes on the last row, so
+    // two comments with code between them (e.g. /* a */ x; /* b */) get a new comment around them instead. On a
+    // single row the start and end can't overlap (e.g. /*/ isn't a comment)
+    let is_commented = first.trim_start().starts_with(start)
+        && last.trim_end().ends_with(end)
+        && (start_row != end_row || start.len() + end.len() <= first.trim().len())
+        && {
+            let text = input_area.lines()[start_row..=end_row].join("\n");
+            let text = text.trim();
+            !text[start.len()..text.len() - end.len()].contains(end)
+        };
+    let (new_first, new_last) = match is_commented {
+        true => {
+            let first_text = first.trim_start().strip_prefix(start).unwrap_or_default();
+            let first_text = first_text.strip_prefix(' ').unwrap_or(first_text);
+            let new_first = format!("{first_indent}{first_text}");
+            // The first and last rows can be the same, so the end is taken off what is left of the first row
+            let last = match start_row == end_row {
+                true => new_first.clone(),
+                false => last.clone(),
+            };
+            let last_text = last.trim_end();
+            let last_text = last_text.strip_suffix(end).unwrap_or(last_text);
+            (
+                new_first,
+                last_text.strip_suffix(' ').unwrap_or(last_text).to_string(),
+            )
+        }
+        false => {
+            let new_first = format!("{first_indent}{start} {}", first.trim_start());
+            let last = match start_row == end_row {
+                true => new_first.clone(),
+                false => last.clone(),
+            };
+            (new_first, format!("{last} {end}"))
+        }
+    };
+    replace_line(input_area, start_row, &new_first);
+    replace_line(input_area, end_row, &new_last);
+    jump_to(input_area, row, col);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config_handler::new_config_parser, language_handler::detect_language};
+
+    fn toggle_block(lines: &[&str]) -> Vec<String> {
+        let language = detect_language(&new_config_parser(), &"a.c".to_string(), "");
+        let mut input_area = TextArea::from(lines.iter().map(|line| line.to_string()));
+        toggle_block_comment(&mut input_area, 0, lines.len() - 1, &language);
+        input_area.lines().to_vec()
+    }
+
+    #[test]
+    fn block_comment_round_trip() {
+        let lines = ["    int x;", "    int y;"];
+        let commented = toggle_block(&lines);
+        assert_eq!(commented, ["    /* int x;", "    int y; */"]);
+        let commented: Vec<&str> = commented.iter().map(String::as_str).collect();
+        assert_eq!(toggle_block(&commented), lines);
+    }
+
+    #[test]
+    fn separate_comments_are_wrapped() {
+        assert_eq!(
+            toggle_block(&["/* a */", "int x;", "/* b */"]),
+            ["/* /* a */", "int x;", "/* b */ */"]
+        );
+        assert_eq!(
+            toggle_block(&["/* a */ x; /* b */"]),
+            ["/* /* a */ x; /* b */ */"]
+        );
+    }
+
+    #[test]
+    fn overlapping_tokens_are_not_a_comment() {
+        assert_eq!(toggle_block(&["/*/"]), ["/* /*/ */"]);
+    }
+}
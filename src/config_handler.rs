@@ -1,55 +1,203 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
 use configparser::ini::Ini;
-use dirs::home_dir;
+use dirs::{data_local_dir, home_dir, state_dir};
 use ratatui::style::Style;
 use tui_textarea::{CursorMove, TextArea};
 
-pub fn parse_config() -> Ini {
-    let lesser_config_path = "\\.config\\recoilless\\rcl_config.txt";
-    let mut config_path = home_dir().unwrap().into_os_string();
-    // Join (lesser_)config_path into one var
-    config_path.push(&lesser_config_path);
-    let mut config_main = Ini::new();
-    let _config = config_main.load(&config_path);
-    return config_main;
+use crate::{editorconfig_handler::glob_matches, language_handler::LanguageStruct};
+
+// Get the directory the config file (and any other saved editor data) is stored in
+pub fn get_config_dir() -> PathBuf {
+    home_dir().unwrap().join(".config").join("recoilless")
+}
+
+// Get the directory state kept between sessions (e.g. marks) is stored in
+// This is ~/.local/state/recoilless on Linux, and the local app data directory on other systems
+pub fn get_state_dir() -> PathBuf {
+    match state_dir().or_else(data_local_dir) {
+        Some(dir) => dir.join("recoilless"),
+        None => get_config_dir().join("state"),
+    }
+}
+
+pub fn get_config_path() -> PathBuf {
+    get_config_dir().join("rcl_config.txt")
+}
+
+// Make a parser for the config file's format. Comments have to be on their own line, so that values can have ; and #
+// in them (e.g. a language's comment tokens)
+pub fn new_config_parser() -> Ini {
+    let mut config = Ini::new();
+    config.set_inline_comment_symbols(Some(&[]));
+    config
 }
 
-pub fn run_config(input_area: &mut TextArea) {
-    let config = parse_config();
+// Parse the config file, along with the error from parsing it if it can't be parsed (a missing config file isn't an
+// error). The config is parsed once each time it is loaded, and passed to everything that reads options from it
+pub fn parse_config() -> (Ini, Option<String>) {
+    let config_path = get_config_path();
+    let mut config_main = new_config_parser();
+    if !config_path.exists() {
+        return (config_main, None);
+    }
+    let error = config_main.load(&config_path).err().map(|error| {
+        // Parse errors come after "couldn't read <path>: ", which isn't needed to find them
+        let error = match error.split_once(": line ") {
+            Some((_, line_error)) => format!("line {line_error}"),
+            None => error,
+        };
+        format!("Config error: {error}")
+    });
+    (config_main, error)
+}
+
+// Get a warning for each option in the config with a value that can't be used, which is left out in its place
+pub fn get_config_warnings(config: &Ini) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let mut sections: Vec<(&String, _)> = config.get_map_ref().iter().collect();
+    sections.sort_by_key(|&(section, _)| section);
+    for (section, options) in sections {
+        let mut keys: Vec<&String> = options.keys().collect();
+        keys.sort();
+        for key in keys {
+            let value = options[key].as_deref().unwrap_or("");
+            let expected = match key.as_str() {
+                "linenumber" | "cursorstart" | "hardtab" | "autopairs" | "wrap" | "autosave"
+                | "autosavefocus"
+                    if value != "true" && value != "false" =>
+                {
+                    "true or false"
+                }
+                "tablength" if !value.parse::<u8>().is_ok_and(|value| value > 0) => {
+                    "a number from 1 to 255"
+                }
+                "wrapcolumn" | "textwidth" | "messagetimeout" | "autosavedelay"
+                    if !value.parse::<usize>().is_ok_and(|value| value > 0) =>
+                {
+                    "a number above 0"
+                }
+                "wrapmovement" if value != "screen" && value != "line" => "screen or line",
+                _ => continue,
+            };
+            warnings.push(format!(
+                "Config warning: {key} in [{section}] should be {expected}, not \"{value}\""
+            ));
+        }
+    }
+    warnings
+}
+
+// Get when the config file was last changed, to tell when it needs to be applied again
+pub fn get_config_modified() -> Option<SystemTime> {
+    fs::metadata(get_config_path())
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+// Check whether a file's path matches a glob. Globs without a slash match the file's name, and ones with a slash
+// match the end of its path. Both are matched in lowercase
+pub fn glob_matches_path(pattern: &str, file_path: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let file_path = file_path.to_lowercase().replace('\\', "/");
+    match pattern.contains('/') {
+        true => glob_matches(
+            &format!("**/{}", pattern.trim_start_matches('/')),
+            &file_path,
+        ),
+        false => {
+            let file_name = Path::new(&file_path)
+                .file_name()
+                .map(|file_name| file_name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            glob_matches(&pattern, &file_name)
+        }
+    }
+}
+
+// Get a config option for a file. The file's language gives the default, which is overridden by [main], which is
+// overridden by a [lang.name] section for the file's language (by its id or its extension), which is overridden by
+// [glob."pattern"] sections matching the file
+pub fn get_config_value(
+    config: &Ini,
+    key: &str,
+    file_path: &String,
+    language: &LanguageStruct,
+) -> Option<String> {
+    let mut value = language.defaults.get(key).cloned();
+    if let Some(main_value) = config.get("main", key) {
+        value = Some(main_value);
+    }
+    let extension = Path::new(file_path)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    for language in [extension, language.id.clone()] {
+        if let Some(language_value) = config.get(&format!("lang.{language}"), key) {
+            value = Some(language_value);
+        }
+    }
+    let mut globs: Vec<String> = config
+        .sections()
+        .into_iter()
+        .filter(|section| section.starts_with("glob."))
+        .collect();
+    globs.sort();
+    for section in globs {
+        let pattern = section["glob.".len()..].trim_matches('"');
+        let is_match = glob_matches_path(pattern, file_path);
+        if let Some(glob_value) = config.get(&section, key).filter(|_| is_match) {
+            value = Some(glob_value);
+        }
+    }
+    value
+}
+
+pub fn run_config(
+    input_area: &mut TextArea,
+    config: &Ini,
+    file_path: &String,
+    language: &LanguageStruct,
+) {
     let mut linenumber = "true".to_string();
     let mut hardtab = "false".to_string();
     let mut _tablength_string = "empty".to_string();
     let mut tablength_int: u8 = 0;
     let mut cursorstart = "true".to_string();
     // Get linenumber from config if applicable
-    match config.get("main", "linenumber") {
+    match get_config_value(config, "linenumber", file_path, language) {
         Some(_) => {
-            linenumber = config.get("main", "linenumber").unwrap();
+            linenumber = get_config_value(config, "linenumber", file_path, language).unwrap();
         }
         None => {
             // Pass
         }
     };
-    match config.get("main", "cursorstart") {
+    match get_config_value(config, "cursorstart", file_path, language) {
         Some(_) => {
-            cursorstart = config.get("main", "cursorstart").unwrap();
+            cursorstart = get_config_value(config, "cursorstart", file_path, language).unwrap();
         }
         None => {
             // Pass
         }
     }
     // Get hardtab from config if applicable
-    match config.get("main", "hardtab") {
+    match get_config_value(config, "hardtab", file_path, language) {
         Some(_) => {
-            hardtab = config.get("main", "hardtab").unwrap();
+            hardtab = get_config_value(config, "hardtab", file_path, language).unwrap();
         }
         None => {
             // Pass
         }
     };
     // Get tab length from config if applicable
-    match config.get("main", "tablength") {
+    match get_config_value(config, "tablength", file_path, language) {
         Some(_) => {
-            _tablength_string = config.get("main", "tablength").unwrap();
+            _tablength_string = get_config_value(config, "tablength", file_path, language).unwrap();
             match _tablength_string.parse::<u8>() {
                 Ok(ok_res) => tablength_int = ok_res,
                 Err(_) => {
@@ -65,7 +213,7 @@ pub fn run_config(input_area: &mut TextArea) {
     // Set config options if able/applicable
     match linenumber.as_str() {
         "false" => {
-            // Pass
+            input_area.remove_line_number();
         }
         &_ => {
             input_area.set_line_number_style(Style::default().fg(ratatui::style::Color::LightCyan));
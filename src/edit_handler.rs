@@ -0,0 +1,111 @@
+use tui_textarea::{CursorMove, TextArea};
+
+// Move the cursor count times, stopping once it can't move any further (e.g. at the end of the file)
+pub fn move_cursor_count(input_area: &mut TextArea, cursor_move: CursorMove, count: usize) {
+    for _ in 0..count {
+        let cursor = input_area.cursor();
+        input_area.move_cursor(cursor_move);
+        if input_area.cursor() == cursor {
+            break;
+        }
+    }
+}
+
+// Count the characters in the input_area (newlines included)
+fn count_chars(input_area: &TextArea) -> usize {
+    let lines = input_area.lines();
+    lines.iter().map(|line| line.chars().count()).sum::<usize>() + lines.len() - 1
+}
+
+// Run a delete that works forward from the cursor count times, as a single edit so it is undone in one step
+pub fn repeat_delete<'a>(
+    input_area: &mut TextArea<'a>,
+    count: usize,
+    should_yank: bool,
+    delete: fn(&mut TextArea<'a>) -> bool,
+) {
+    if count <= 1 {
+        delete(input_area);
+        return;
+    }
+    // Run the delete on a copy of the input_area to find out how much it removes. Each delete takes at least one
+    // character, so the count is no more than the characters in the file, and it stops once nothing is left to delete
+    let mut preview = input_area.clone();
+    for _ in 0..count.min(count_chars(input_area)) {
+        if !delete(&mut preview) {
+            break;
+        }
+    }
+    let deleted = count_chars(input_area) - count_chars(&preview);
+    let yank_text = input_area.yank_text();
+    input_area.delete_str(deleted);
+    if !should_yank {
+        input_area.set_yank_text(yank_text);
+    }
+}
+
+// Delete the contents of count lines starting from the cursor's line, leaving one empty line
+pub fn delete_lines(input_area: &mut TextArea, count: usize) {
+    input_area.move_cursor(CursorMove::Head);
+    match count {
+        0 | 1 => {
+            input_area.delete_line_by_end();
+        }
+        _ => {
+            input_area.start_selection();
+            move_cursor_count(input_area, CursorMove::Down, count - 1);
+            input_area.move_cursor(CursorMove::End);
+            input_area.cut();
+        }
+    }
+}
+
+// Paste the yanked text count times as a single edit
+pub fn paste_count(input_area: &mut TextArea, count: usize) {
+    match count {
+        0 | 1 => {
+            input_area.paste();
+        }
+        _ => {
+            input_area.insert_str(input_area.yank_text().repeat(count));
+        }
+    }
+}
+
+// Move the cursor to a (row, col) position, even when it is past what CursorMove::Jump can reach
+pub fn jump_to(input_area: &mut TextArea, row: usize, col: usize) {
+    match (u16::try_from(row), u16::try_from(col)) {
+        (Ok(row), Ok(col)) => input_area.move_cursor(CursorMove::Jump(row, col)),
+        _ => {
+            let row = row.min(input_area.lines().len() - 1);
+            let col = col.min(input_area.lines()[row].chars().count());
+            input_area.move_cursor(CursorMove::Top);
+            move_cursor_count(input_area, CursorMove::Down, row);
+            input_area.move_cursor(CursorMove::Head);
+            move_cursor_count(input_area, CursorMove::Forward, col);
+        }
+    }
+}
+
+// Replace the text of a line, leaving the cursor at the end of it
+pub fn replace_line(input_area: &mut TextArea, row: usize, text: &str) {
+    if input_area.lines()[row] == text {
+        return;
+    }
+    replace_rows(input_area, row, row, text);
+}
+
+// Replace the text of a range of rows (text can have newlines in it), leaving the cursor at the end of it
+pub fn replace_rows(input_area: &mut TextArea, start_row: usize, end_row: usize, text: &str) {
+    input_area.cancel_selection();
+    jump_to(input_area, start_row, 0);
+    input_area.start_selection();
+    jump_to(
+        input_area,
+        end_row,
+        input_area.lines()[end_row].chars().count(),
+    );
+    input_area.delete_str(0);
+    input_area.cancel_selection();
+    input_area.insert_str(text);
+}
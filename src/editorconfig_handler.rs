@@ -0,0 +1,379 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{self, Path, PathBuf},
+};
+
+use tui_textarea::TextArea;
+
+use crate::file_handler::FileSettingsStruct;
+
+// Setup the struct which holds one section of an .editorconfig file
+struct SectionStruct {
+    // The glob the section applies to, None for the preamble before the first section
+    name: Option<String>,
+    properties: Vec<(String, String)>,
+}
+
+// Get the properties that apply to a file from the .editorconfig files in its directory and the ones above it
+// Files closer to the edited file override ones further up, and a file with root = true stops the search
+pub fn get_editorconfig(file_path: &String) -> HashMap<String, String> {
+    let file_path = path::absolute(file_path).unwrap_or_else(|_| PathBuf::from(file_path));
+    let mut config_files = Vec::new();
+    let mut dir = file_path.parent();
+    while let Some(current_dir) = dir {
+        if let Ok(config_text) = fs::read_to_string(current_dir.join(".editorconfig")) {
+            // The preamble is always the first section
+            let is_root = parse_editorconfig(&config_text)[0]
+                .properties
+                .iter()
+                .any(|(key, value)| key == "root" && value == "true");
+            config_files.push((current_dir.to_path_buf(), config_text));
+            if is_root {
+                break;
+            }
+        }
+        dir = current_dir.parent();
+    }
+    let mut properties = HashMap::new();
+    for (config_dir, config_text) in config_files.iter().rev() {
+        let relative_path = match file_path.strip_prefix(config_dir) {
+            Ok(relative_path) => relative_path.to_string_lossy().replace('\\', "/"),
+            Err(_) => continue,
+        };
+        for section in parse_editorconfig(config_text) {
+            let is_match = section
+                .name
+                .is_some_and(|name| section_matches(&name, &relative_path));
+            if !is_match {
+                continue;
+            }
+            for (key, value) in section.properties {
+                match value.as_str() {
+                    // unset takes back a property set by an earlier section or file
+                    "unset" => {
+                        properties.remove(&key);
+                    }
+                    _ => {
+                        properties.insert(key, value);
+                    }
+                }
+            }
+        }
+    }
+    properties
+}
+
+// Split an .editorconfig file into its sections, in order
+// Keys are made lowercase, and so are values (which are case insensitive for every property used here)
+fn parse_editorconfig(config_text: &str) -> Vec<SectionStruct> {
+    let mut sections = vec![SectionStruct {
+        name: None,
+        properties: Vec::new(),
+    }];
+    for line in config_text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(section) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            sections.push(SectionStruct {
+                name: Some(section.to_string()),
+                properties: Vec::new(),
+            });
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            let property = (key.trim().to_lowercase(), value.trim().to_lowercase());
+            sections.last_mut().unwrap().properties.push(property);
+        }
+    }
+    sections
+}
+
+// Check whether a section name matches a path relative to the .editorconfig file
+// Names without a slash match the file name in any directory, names with one match from the .editorconfig's directory
+fn section_matches(section: &str, relative_path: &str) -> bool {
+    expand_braces(section)
+        .iter()
+        .any(|pattern| match pattern.contains('/') {
+            true => glob_matches(pattern.strip_prefix('/').unwrap_or(pattern), relative_path),
+            false => {
+                let file_name = Path::new(relative_path)
+                    .file_name()
+                    .map(|file_name| file_name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                glob_matches(pattern, &file_name)
+            }
+        })
+}
+
+// Expand {a,b} alternatives and {1..3} number ranges into a list of patterns without braces. Braces and commas
+// after a \ are just characters
+fn expand_braces(pattern: &str) -> Vec<String> {
+    // Find the first brace, the brace that closes it, and the commas at its level between them
+    let mut open = None;
+    let mut close = None;
+    let mut depth = 0;
+    let mut commas = Vec::new();
+    let mut is_escaped = false;
+    for (i, c) in pattern.char_indices() {
+        match c {
+            _ if is_escaped => is_escaped = false,
+            '\\' => is_escaped = true,
+            '{' => {
+                open.get_or_insert(i);
+                depth += 1;
+            }
+            '}' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(i);
+                    break;
+                }
+            }
+            ',' if depth == 1 => commas.push(i),
+            _ => {
+                // Pass
+            }
+        }
+    }
+    let (open, close) = match (open, close) {
+        (Some(open), Some(close)) => (open, close),
+        _ => return vec![pattern.to_string()],
+    };
+    let inside = &pattern[open + 1..close];
+    let mut options: Vec<String> = Vec::new();
+    let mut last = open + 1;
+    for comma in commas {
+        options.push(pattern[last..comma].to_string());
+        last = comma + 1;
+    }
+    options.push(pattern[last..close].to_string());
+    if options.len() == 1 {
+        let range = inside
+            .split_once("..")
+            .and_then(|(start, end)| Some((start.parse::<i64>().ok()?, end.parse::<i64>().ok()?)));
+        match range {
+            Some((start, end)) if start <= end && end - start <= 1000 => {
+                options = (start..=end).map(|number| number.to_string()).collect();
+            }
+            // Braces around a single option that isn't a range are just characters
+            _ => {
+                return expand_braces(&pattern[close + 1..])
+                    .into_iter()
+                    .map(|rest| format!("{}{{{inside}}}{rest}", &pattern[..open]))
+                    .collect();
+            }
+        }
+    }
+    let mut patterns = Vec::new();
+    for option in options {
+        let expanded = format!("{}{option}{}", &pattern[..open], &pattern[close + 1..]);
+        patterns.extend(expand_braces(&expanded));
+    }
+    patterns
+}
+
+// Match a path against a glob: * matches anything but /, ** matches anything, ? matches one character and
+// [abc], [a-z] and [!abc] match one character from (or not from) a set. A character after a \ is matched as it is
+pub fn glob_matches(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let path: Vec<char> = path.chars().collect();
+    glob_matches_from(&pattern, &path)
+}
+
+fn glob_matches_from(pattern: &[char], path: &[char]) -> bool {
+    match pattern.first() {
+        None => path.is_empty(),
+        // **/ can also match no directories at all, so a/**/b matches a/b
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            (pattern.get(2) == Some(&'/') && glob_matches_from(&pattern[3..], path))
+                || (0..=path.len()).any(|skip| glob_matches_from(&pattern[2..], &path[skip..]))
+        }
+        Some('*') => (0..=path.len())
+            .take_while(|&skip| skip == 0 || path[skip - 1] != '/')
+            .any(|skip| glob_matches_from(&pattern[1..], &path[skip..])),
+        Some('?') => {
+            !path.is_empty() && path[0] != '/' && glob_matches_from(&pattern[1..], &path[1..])
+        }
+        Some('[') => match pattern.iter().skip(1).position(|&c| c == ']') {
+            Some(end) if !path.is_empty() => {
+                let set = &pattern[1..end + 1];
+                let (is_negated, set) = match set.first() {
+                    Some('!') => (true, &set[1..]),
+                    _ => (false, set),
+                };
+                let mut is_in_set = false;
+                let mut i = 0;
+                while i < set.len() {
+                    match set.get(i + 1) == Some(&'-') && i + 2 < set.len() {
+                        true => {
+                            is_in_set |= set[i] <= path[0] && path[0] <= set[i + 2];
+                            i += 3;
+                        }
+                        false => {
+                            is_in_set |= set[i] == path[0];
+                            i += 1;
+                        }
+                    }
+                }
+                is_in_set != is_negated && glob_matches_from(&pattern[end + 2..], &path[1..])
+            }
+            Some(_) => false,
+            // A [ without a ] is just a character
+            None => path.first() == Some(&'[') && glob_matches_from(&pattern[1..], &path[1..]),
+        },
+        Some('\\') if pattern.len() > 1 => {
+            path.first() == Some(&pattern[1]) && glob_matches_from(&pattern[2..], &path[1..])
+        }
+        Some(&c) => path.first() == Some(&c) && glob_matches_from(&pattern[1..], &path[1..]),
+    }
+}
+
+// Apply the properties that change how the file is read and saved
+pub fn apply_editorconfig_file(
+    properties: &HashMap<String, String>,
+    file_settings: &mut FileSettingsStruct,
+) {
+    if let Some(end_of_line) = properties.get("end_of_line") {
+        match end_of_line.as_str() {
+            "lf" => file_settings.end_of_line = Some("\n"),
+            "crlf" => file_settings.end_of_line = Some("\r\n"),
+            "cr" => file_settings.end_of_line = Some("\r"),
+            _ => {
+                // Pass
+            }
+        }
+    }
+    if let Some(charset) = properties.get("charset") {
+        match charset.as_str() {
+            "utf-8" | "utf-8-bom" | "latin1" | "utf-16be" | "utf-16le" => {
+                file_settings.charset = Some(charset.clone());
+            }
+            _ => {
+                // Pass
+            }
+        }
+    }
+    match properties
+        .get("trim_trailing_whitespace")
+        .map(String::as_str)
+    {
+        Some("true") => file_settings.trim_trailing_whitespace = true,
+        Some("false") => file_settings.trim_trailing_whitespace = false,
+        _ => {
+            // Pass
+        }
+    }
+    match properties.get("insert_final_newline").map(String::as_str) {
+        Some("true") => file_settings.insert_final_newline = Some(true),
+        Some("false") => file_settings.insert_final_newline = Some(false),
+        _ => {
+            // Pass
+        }
+    }
+    if let Some(max_line_length) = properties.get("max_line_length") {
+        file_settings.max_line_length = max_line_length.parse().ok();
+    }
+}
+
+// Apply the indentation properties, over both the config and the indentation detected in the file
+pub fn apply_editorconfig_indent(properties: &HashMap<String, String>, input_area: &mut TextArea) {
+    match properties.get("indent_style").map(String::as_str) {
+        Some("tab") => input_area.set_hard_tab_indent(true),
+        Some("space") => input_area.set_hard_tab_indent(false),
+        _ => {
+            // Pass
+        }
+    }
+    let parse_width = |key: &str| -> Option<u8> {
+        properties
+            .get(key)
+            .and_then(|width| width.parse().ok())
+            .filter(|&width| width > 0)
+    };
+    // indent_size = tab means a level is a tab, so its width is tab_width. Otherwise each falls back on the other
+    let indent_size = match properties.get("indent_size").map(String::as_str) {
+        Some("tab") => parse_width("tab_width"),
+        _ => parse_width("indent_size"),
+    };
+    let tab_width = parse_width("tab_width");
+    let width = match input_area.hard_tab_indent() {
+        true => tab_width.or(indent_size),
+        false => indent_size.or(tab_width),
+    };
+    if let Some(width) = width {
+        input_area.set_tab_length(width);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand_braces, glob_matches};
+
+    #[test]
+    fn expands_alternatives() {
+        assert_eq!(expand_braces("*.{js,py}"), ["*.js", "*.py"]);
+        assert_eq!(expand_braces("{a,b}.{c,d}"), ["a.c", "a.d", "b.c", "b.d"]);
+        assert_eq!(expand_braces("{,.}rc"), ["rc", ".rc"]);
+    }
+
+    #[test]
+    fn expands_nested_braces() {
+        assert_eq!(expand_braces("{a,{b,c}}.txt"), ["a.txt", "b.txt", "c.txt"]);
+        assert_eq!(expand_braces("x{a,b{1,2}}"), ["xa", "xb1", "xb2"]);
+    }
+
+    #[test]
+    fn expands_number_ranges() {
+        assert_eq!(expand_braces("file{1..3}"), ["file1", "file2", "file3"]);
+        assert_eq!(expand_braces("{-1..1}"), ["-1", "0", "1"]);
+        assert_eq!(expand_braces("{1..1}"), ["1"]);
+    }
+
+    #[test]
+    fn keeps_braces_that_are_not_expanded() {
+        assert_eq!(expand_braces("{single}.txt"), ["{single}.txt"]);
+        assert_eq!(expand_braces("{a,b"), ["{a,b"]);
+        assert_eq!(expand_braces("a}{b,c}"), ["a}b", "a}c"]);
+        assert_eq!(expand_braces("\\{a,b\\}"), ["\\{a,b\\}"]);
+        assert_eq!(expand_braces("{a\\,b,c}"), ["a\\,b", "c"]);
+    }
+
+    #[test]
+    fn matches_stars() {
+        assert!(glob_matches("*.rs", "main.rs"));
+        assert!(!glob_matches("*.rs", "src/main.rs"));
+        assert!(glob_matches("**.rs", "src/main.rs"));
+        assert!(glob_matches("**/*.rs", "src/bin/main.rs"));
+        assert!(glob_matches("**/*.rs", "main.rs"));
+        assert!(glob_matches("a/**/z.c", "a/z.c"));
+        assert!(glob_matches("a/**/z.c", "a/b/c/z.c"));
+        assert!(!glob_matches("a/**/z.c", "b/z.c"));
+        assert!(glob_matches("*", ""));
+    }
+
+    #[test]
+    fn matches_single_characters_and_sets() {
+        assert!(glob_matches("?.c", "a.c"));
+        assert!(!glob_matches("?.c", "ab.c"));
+        assert!(!glob_matches("a?b", "a/b"));
+        assert!(glob_matches("[a-c].txt", "b.txt"));
+        assert!(!glob_matches("[a-c].txt", "d.txt"));
+        assert!(glob_matches("[!a].txt", "b.txt"));
+        assert!(!glob_matches("[!a].txt", "a.txt"));
+        assert!(glob_matches("[abc", "[abc"));
+    }
+
+    #[test]
+    fn matches_escaped_characters() {
+        assert!(glob_matches("\\*.txt", "*.txt"));
+        assert!(!glob_matches("\\*.txt", "a.txt"));
+        assert!(glob_matches("a\\,b", "a,b"));
+        assert!(glob_matches("\\[x\\]", "[x]"));
+    }
+}
@@ -1,239 +1,213 @@
 use std::{
     fs,
     io::{self, Write},
+    time::SystemTime,
 };
-use tui_textarea::TextArea;
+use tui_textarea::{CursorMove, TextArea};
+
+use crate::edit_handler::jump_to;
+
+// Setup the struct which holds how a file is read and saved, from what was found in it and its .editorconfig
+pub struct FileSettingsStruct {
+    // The newline written between lines, None until the file has been read (which uses what the file already has)
+    pub end_of_line: Option<&'static str>,
+    // One of utf-8, utf-8-bom, latin1, utf-16be or utf-16le, None until the file has been read
+    pub charset: Option<String>,
+    pub trim_trailing_whitespace: bool,
+    // Some(true) makes sure the file ends with a newline when saved, and Some(false) makes sure it doesn't
+    pub insert_final_newline: Option<bool>,
+    pub max_line_length: Option<usize>,
+}
+
+pub fn new_file_settings() -> FileSettingsStruct {
+    FileSettingsStruct {
+        end_of_line: None,
+        charset: None,
+        trim_trailing_whitespace: false,
+        insert_final_newline: None,
+        max_line_length: None,
+    }
+}
 
 // Fetches the file's size
 pub fn get_file_size(file_path: &String) -> String {
-    let mut file_size = fs::File::open(&file_path)
+    let mut file_size = fs::File::open(file_path)
         .expect("Failed to open file in get_file_size")
         .metadata()
         .expect("Failed to get file metadata in get_file_size")
         .len()
         .to_string();
     file_size.push_str(" Bytes Saved ");
-    return file_size;
+    file_size
 }
 
-// Save the current file (if is_modified is true)
-pub fn save_file(is_modified: &bool, file_path: &String, input_area: &mut TextArea) {
-    match is_modified {
-        true => {
-            let mut writer = io::BufWriter::new(
-                fs::File::create(&file_path).expect("Failed to create BufWriter!"),
-            );
-            for l in input_area.lines() {
-                writer
-                    .write_all(l.as_bytes())
-                    .expect("Error when writing to file!");
-                writer
-                    .write_all(b"\n")
-                    .expect("Error when writing to file!");
+// Get when the file was last changed, None if it can't be read
+pub fn get_file_modified(file_path: &str) -> Option<SystemTime> {
+    fs::metadata(file_path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+// The start and multiplier of 64 bit FNV-1a hashes
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+// Add bytes to a 64 bit FNV-1a hash. Unlike Rust's DefaultHasher this gives the same hash on every version of Rust,
+// so it can be used for state that is saved on disk
+fn add_to_hash(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+// Get a name for files that store state about a file (e.g. its marks), made from a hash of its path
+pub fn get_state_name(file_path: &String) -> String {
+    format!("{:016x}.txt", add_to_hash(FNV_OFFSET, file_path.as_bytes()))
+}
+
+// Get a hash of the lines in a file, used to check the file hasn't changed since state about it was saved
+pub fn get_content_hash(lines: &[String]) -> u64 {
+    // Each line is followed by a byte that is never in utf-8 text, so lines split in different places hash differently
+    lines.iter().fold(FNV_OFFSET, |hash, line| {
+        add_to_hash(add_to_hash(hash, line.as_bytes()), &[0xFF])
+    })
+}
+
+// Read a file's contents using its charset (or the one its byte order mark shows, or utf-8), with the newlines
+// changed to \n. The charset and newline found are kept in file_settings, unless they were already set
+pub fn read_file(file_path: &String, file_settings: &mut FileSettingsStruct) -> String {
+    let bytes = fs::read(file_path).expect("Failed to unwrap file contents");
+    let found_charset = match bytes.as_slice() {
+        [0xEF, 0xBB, 0xBF, ..] => "utf-8-bom",
+        [0xFE, 0xFF, ..] => "utf-16be",
+        [0xFF, 0xFE, ..] => "utf-16le",
+        _ => "utf-8",
+    };
+    let charset = file_settings
+        .charset
+        .get_or_insert_with(|| found_charset.to_string());
+    let contents = match charset.as_str() {
+        "latin1" => bytes.iter().map(|&byte| byte as char).collect(),
+        "utf-16be" | "utf-16le" => {
+            let is_big_endian = charset == "utf-16be";
+            let pairs = bytes.chunks_exact(2);
+            let is_odd_length = !pairs.remainder().is_empty();
+            let mut units: Vec<u16> = pairs
+                .map(|pair| match is_big_endian {
+                    true => u16::from_be_bytes([pair[0], pair[1]]),
+                    false => u16::from_le_bytes([pair[0], pair[1]]),
+                })
+                .collect();
+            // A byte left over at the end is half a character, so it is read as the replacement character
+            if is_odd_length {
+                units.push(0xFFFD);
             }
-            drop(writer);
-        }
-        false => {
-            // Pass
-        }
+            String::from_utf16_lossy(&units)
+        }
+        _ => String::from_utf8_lossy(&bytes).to_string(),
+    };
+    let contents = contents.strip_prefix('\u{feff}').unwrap_or(&contents);
+    let found_end_of_line = match (contents.contains("\r\n"), contents.contains('\n')) {
+        (true, _) => "\r\n",
+        (false, false) if contents.contains('\r') => "\r",
+        _ => "\n",
+    };
+    let end_of_line = *file_settings.end_of_line.get_or_insert(found_end_of_line);
+    match end_of_line {
+        "\r" => contents.replace("\r\n", "\n").replace('\r', "\n"),
+        _ => contents.replace("\r\n", "\n"),
     }
 }
 
-// Convert to file extension into something more readable
-pub fn convert_extension(mut file_type: &str) -> &str {
-    match file_type {
-        "txt" => {
-            file_type = "Text File";
-            return file_type;
-        }
-        // Configuration Files
-        "cfg" => {
-            file_type = "CFG File";
-            return file_type;
-        }
-        "json" => {
-            file_type = "JSON File";
-            return file_type;
-        }
-        "toml" => {
-            file_type = "TOML File";
-            return file_type;
-        }
-        "yaml" => {
-            file_type = "YAML FILE";
-            return file_type;
-        }
-        "ini" => {
-            file_type = "INI File";
-            return file_type;
-        }
-        "csv" => {
-            file_type = "Comma Seperated Values File";
-            return file_type;
-        }
-        // Git Specific Files
-        "gitignore" => {
-            file_type = "Git Ignore File";
-            return file_type;
-        }
-        "gitattributes" => {
-            file_type = "Git Attributes File";
-            return file_type;
-        }
-        // UI/Markup Files
-        "md" => {
-            file_type = "Markdown File";
-            return file_type;
-        }
-        "xml" => {
-            file_type = "XML File";
-            return file_type;
-        }
-        "xaml" => {
-            file_type = "XAML File";
-            return file_type;
-        }
-        "axaml" => {
-            file_type = "AXAML File";
-            return file_type;
-        }
-        "html" => {
-            file_type = "HTML File";
-            return file_type;
-        }
-        "xhtml" => {
-            file_type = "XHTML File";
-            return file_type;
-        }
-        "css" => {
-            file_type = "CSS File";
-            return file_type;
-        }
-        // Script Files
-        "sh" => {
-            file_type = "Shell Script";
-            return file_type;
-        }
-        "ps1" => {
-            file_type = "Powershell Script";
-            return file_type;
-        }
-        "bat" => {
-            file_type = "Batch File";
-            return file_type;
-        }
-        // Programmins Source Files
-        "c" => {
-            file_type = "C Source File";
-            return file_type;
-        }
-        "h" => {
-            file_type = "C/C++ Source File";
-            return file_type;
-        }
-        "cpp" | "C" | "cc" | "cxx" | "c++" | "H" | "hh" | "hpp" | "hxx" | "h++" | "cppm"
-        | "ixx" => {
-            file_type = "C++ Source File";
-            return file_type;
-        }
-        "r" => {
-            file_type = "R Source File";
-            return file_type;
-        }
-        "scala" | "sc" => {
-            file_type = "Scala Source File";
-            return file_type;
-        }
-        "ml" => {
-            file_type = "OCaml File";
-            return file_type;
-        }
-        "mll" => {
-            file_type = "OCamllex File";
-            return file_type;
-        }
-        "gd" => {
-            file_type = "GDScript Source File";
-            return file_type;
-        }
-        "rs" => {
-            file_type = "Rust Source File";
-            return file_type;
-        }
-        "zig" => {
-            file_type = "Zig Source File";
-            return file_type;
-        }
-        "cs" => {
-            file_type = "C# Source File";
-            return file_type;
-        }
-        "fs" => {
-            file_type = "F# Source File";
-            return file_type;
-        }
-        "hs" => {
-            file_type = "Haskell";
-            return file_type;
-        }
-        "erl" => {
-            file_type = "Erlang Source File";
-            return file_type;
-        }
-        "py" => {
-            file_type = "Python Source File";
-            return file_type;
-        }
-        "java" => {
-            file_type = "Java Source File";
-            return file_type;
-        }
-        "go" => {
-            file_type = "GOLANG Source File";
-            return file_type;
-        }
-        "lua" => {
-            file_type = "Lua Source File";
-            return file_type;
-        }
-        "cr" => {
-            file_type = "Crystal Source File";
-            return file_type;
-        }
-        "hx" => {
-            file_type = "Haxe Source File";
-            return file_type;
-        }
-        "swift" => {
-            file_type = "Swift Souce File";
-            return file_type;
-        }
-        "dart" => {
-            file_type = "Dart Source File";
-            return file_type;
-        }
-        "pl" => {
-            file_type = "Perl Source File";
-            return file_type;
-        }
-        "rb" => {
-            file_type = "Ruby Source File";
-            return file_type;
+// Turn text into the bytes for a charset (characters latin1 can't hold are written as ?)
+fn encode_text(text: &str, charset: &str) -> Vec<u8> {
+    match charset {
+        "utf-8-bom" => [&[0xEF, 0xBB, 0xBF], text.as_bytes()].concat(),
+        "latin1" => text
+            .chars()
+            .map(|c| u8::try_from(c).unwrap_or(b'?'))
+            .collect(),
+        "utf-16be" => ["\u{feff}", text]
+            .concat()
+            .encode_utf16()
+            .flat_map(u16::to_be_bytes)
+            .collect(),
+        "utf-16le" => ["\u{feff}", text]
+            .concat()
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect(),
+        _ => text.as_bytes().to_vec(),
+    }
+}
+
+// Change the lines before saving as file_settings asks (trimming trailing whitespace, adding or removing the
+// newline at the end), keeping the cursor where it was. Returns true if anything changed
+pub fn prepare_save(input_area: &mut TextArea, file_settings: &FileSettingsStruct) -> bool {
+    let cursor = input_area.cursor();
+    let mut is_changed = false;
+    input_area.cancel_selection();
+    if file_settings.trim_trailing_whitespace {
+        for row in 0..input_area.lines().len() {
+            let line = &input_area.lines()[row];
+            let trimmed_length = line.trim_end().chars().count();
+            let line_length = line.chars().count();
+            if trimmed_length < line_length {
+                jump_to(input_area, row, trimmed_length);
+                input_area.start_selection();
+                jump_to(input_area, row, line_length);
+                input_area.delete_str(0);
+                input_area.cancel_selection();
+                is_changed = true;
+            }
         }
-        "php" => {
-            file_type = "PHP Source File";
-            return file_type;
+    }
+    // Lines are joined by newlines, so the file ends with a newline when the last line is empty
+    match file_settings.insert_final_newline {
+        Some(true) if !input_area.lines().last().unwrap().is_empty() => {
+            input_area.move_cursor(CursorMove::Bottom);
+            input_area.move_cursor(CursorMove::End);
+            input_area.insert_newline();
+            is_changed = true;
+        }
+        Some(false) => {
+            while input_area.lines().len() > 1 && input_area.lines().last().unwrap().is_empty() {
+                let row = input_area.lines().len() - 2;
+                jump_to(input_area, row, input_area.lines()[row].chars().count());
+                input_area.delete_next_char();
+                is_changed = true;
+            }
         }
-        "js" => {
-            file_type = "JavaScript Source File";
-            return file_type;
+        _ => {
+            // Pass
         }
-        "ts" => {
-            file_type = "TypeScript Source File";
-            return file_type;
+    }
+    jump_to(input_area, cursor.0, cursor.1);
+    is_changed
+}
+
+// Save the current file (if is_modified is true), with the newlines and charset from file_settings
+pub fn save_file(
+    is_modified: &bool,
+    file_path: &String,
+    input_area: &mut TextArea,
+    file_settings: &FileSettingsStruct,
+) -> io::Result<()> {
+    match is_modified {
+        true => {
+            let mut writer = io::BufWriter::new(fs::File::create(file_path)?);
+            // Lines are joined by newlines, so a file that ended with a newline (and so has an empty last line) keeps
+            // just the one newline at the end, and insert_final_newline decides whether there is one
+            let text = input_area
+                .lines()
+                .join(file_settings.end_of_line.unwrap_or("\n"));
+            let charset = file_settings.charset.as_deref().unwrap_or("utf-8");
+            writer.write_all(&encode_text(&text, charset))?;
+            writer.flush()?;
         }
-        &_ => {
-            return file_type;
+        false => {
+            // Pass
         }
     }
+    Ok(())
 }
@@ -0,0 +1,85 @@
+use std::{
+    io::{Read, Write},
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use tui_textarea::TextArea;
+
+use crate::edit_handler::jump_to;
+
+// How long a formatter can run before it is stopped, and how often it is checked on until then
+const FORMAT_TIMEOUT: Duration = Duration::from_secs(10);
+const FORMAT_CHECK_INTERVAL: Duration = Duration::from_millis(10);
+
+// Run text through a formatter command (which reads the file on stdin and writes the formatted file to stdout)
+// Returns None if the formatter failed, or was stopped for taking longer than FORMAT_TIMEOUT
+pub fn format_text(formatter: &str, text: String) -> Option<String> {
+    let mut command = match cfg!(windows) {
+        true => Command::new("cmd"),
+        false => Command::new("sh"),
+    };
+    command.arg(match cfg!(windows) {
+        true => "/C",
+        false => "-c",
+    });
+    let mut child = command
+        .arg(formatter)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // Written from another thread, so a formatter that writes before it has read everything can't get stuck
+        thread::spawn(move || stdin.write_all(text.as_bytes()));
+    }
+    // Read from another thread as well, so a formatter that writes more than the pipe holds can still finish
+    let mut stdout = child.stdout.take()?;
+    let reader = thread::spawn(move || {
+        let mut output = Vec::new();
+        stdout.read_to_end(&mut output).map(|_| output)
+    });
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if started.elapsed() < FORMAT_TIMEOUT => thread::sleep(FORMAT_CHECK_INTERVAL),
+            // A formatter that hangs is stopped, so it doesn't keep running in the background
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    };
+    match reader.join() {
+        Ok(Ok(output)) if status.success() => Some(String::from_utf8_lossy(&output).to_string()),
+        _ => None,
+    }
+}
+
+// Replace the lines with the formatter's output, keeping the cursor where it was
+pub fn apply_formatted_text(input_area: &mut TextArea, formatted: String) {
+    if formatted == input_area.lines().join("\n") {
+        return;
+    }
+    let (row, col) = input_area.cursor();
+    input_area.select_all();
+    input_area.delete_str(0);
+    input_area.cancel_selection();
+    input_area.insert_str(formatted);
+    jump_to(input_area, row, col);
+}
+
+// Run the lines through a formatter command and replace them with its output, returns false if the formatter failed
+pub fn run_formatter(input_area: &mut TextArea, formatter: &str) -> bool {
+    match format_text(formatter, input_area.lines().join("\n")) {
+        Some(formatted) => {
+            apply_formatted_text(input_area, formatted);
+            true
+        }
+        None => false,
+    }
+}
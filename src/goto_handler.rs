@@ -0,0 +1,114 @@
+// Turn a go to position into a (row, col) position in the lines
+// Accepts "line", "line:col", "+N"/"-N" (lines from the cursor), "N%" (percent of the way through the file)
+// and "bN" (byte offset from the start of the file). Lines and columns are counted from 1
+pub fn parse_goto(text: &str, lines: &[String], cursor: (usize, usize)) -> Option<(usize, usize)> {
+    let text = text.trim();
+    let last_row = lines.len() - 1;
+    if let Some(percent) = text.strip_suffix('%') {
+        let percent: usize = percent.trim().parse().ok()?;
+        let line = (percent.min(100) * lines.len()).div_ceil(100);
+        return Some((line.saturating_sub(1).min(last_row), 0));
+    }
+    if let Some(offset) = text.strip_prefix('b') {
+        return byte_offset_to_position(lines, offset.trim().parse().ok()?);
+    }
+    if let Some(lines_down) = text.strip_prefix('+') {
+        let lines_down: usize = lines_down.trim().parse().ok()?;
+        return Some((cursor.0.saturating_add(lines_down).min(last_row), 0));
+    }
+    if let Some(lines_up) = text.strip_prefix('-') {
+        let lines_up: usize = lines_up.trim().parse().ok()?;
+        return Some((cursor.0.saturating_sub(lines_up), 0));
+    }
+    let mut parts = text.splitn(2, ':');
+    let line: usize = parts.next()?.trim().parse().ok()?;
+    let col: usize = match parts.next() {
+        Some(col) => col.trim().parse().ok()?,
+        None => 1,
+    };
+    let row = line.saturating_sub(1).min(last_row);
+    let col = col.saturating_sub(1).min(lines[row].chars().count());
+    Some((row, col))
+}
+
+// Find the (row, col) position of a byte offset, where each line is followed by a one byte newline
+fn byte_offset_to_position(lines: &[String], mut offset: usize) -> Option<(usize, usize)> {
+    for (row, line) in lines.iter().enumerate() {
+        if offset <= line.len() {
+            // Count the characters that end at or before the offset
+            let col = line
+                .char_indices()
+                .take_while(|(i, c)| i + c.len_utf8() <= offset)
+                .count();
+            return Some((row, col));
+        }
+        offset -= line.len() + 1;
+    }
+    let last_row = lines.len() - 1;
+    Some((last_row, lines[last_row].chars().count()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_goto;
+
+    fn goto(text: &str) -> Option<(usize, usize)> {
+        let lines = ["first", "sëcond", "", "fourth line"].map(String::from);
+        parse_goto(text, &lines, (1, 3))
+    }
+
+    #[test]
+    fn goes_to_lines_and_columns() {
+        assert_eq!(goto("2"), Some((1, 0)));
+        assert_eq!(goto(" 3 "), Some((2, 0)));
+        assert_eq!(goto("2:3"), Some((1, 2)));
+        assert_eq!(goto("4 : 5"), Some((3, 4)));
+    }
+
+    #[test]
+    fn out_of_range_positions_are_clamped() {
+        assert_eq!(goto("0"), Some((0, 0)));
+        assert_eq!(goto("99"), Some((3, 0)));
+        assert_eq!(goto("2:99"), Some((1, 6)));
+        assert_eq!(goto("1:0"), Some((0, 0)));
+        assert_eq!(goto("99999999999999999999999"), None);
+    }
+
+    #[test]
+    fn empty_or_bad_input_goes_nowhere() {
+        assert_eq!(goto(""), None);
+        assert_eq!(goto("   "), None);
+        assert_eq!(goto("abc"), None);
+        assert_eq!(goto("2:"), None);
+        assert_eq!(goto("2:x"), None);
+        assert_eq!(goto("+"), None);
+        assert_eq!(goto("%"), None);
+        assert_eq!(goto("b"), None);
+    }
+
+    #[test]
+    fn goes_relative_to_the_cursor() {
+        assert_eq!(goto("+2"), Some((3, 0)));
+        assert_eq!(goto("+9"), Some((3, 0)));
+        assert_eq!(goto("-1"), Some((0, 0)));
+        assert_eq!(goto("-5"), Some((0, 0)));
+    }
+
+    #[test]
+    fn goes_to_percentages() {
+        assert_eq!(goto("0%"), Some((0, 0)));
+        assert_eq!(goto("50%"), Some((1, 0)));
+        assert_eq!(goto("100%"), Some((3, 0)));
+        assert_eq!(goto("200%"), Some((3, 0)));
+    }
+
+    #[test]
+    fn goes_to_byte_offsets() {
+        assert_eq!(goto("b0"), Some((0, 0)));
+        assert_eq!(goto("b6"), Some((1, 0)));
+        // The ë is two bytes, so an offset in the middle of it stays before it
+        assert_eq!(goto("b8"), Some((1, 1)));
+        assert_eq!(goto("b9"), Some((1, 2)));
+        assert_eq!(goto("b1000"), Some((3, 11)));
+    }
+}
@@ -0,0 +1,244 @@
+use tui_textarea::TextArea;
+
+use crate::{
+    edit_handler::jump_to,
+    language_handler::{get_indent_closers, LanguageStruct},
+};
+
+// Get the whitespace at the start of a line
+pub fn get_line_indent(line: &str) -> &str {
+    &line[..line.len() - line.trim_start().len()]
+}
+
+// Get the indentation for a line made after the cursor, from the line the cursor is on
+fn get_newline_indent(input_area: &TextArea, language: &LanguageStruct) -> String {
+    let (row, col) = input_area.cursor();
+    let line = &input_area.lines()[row];
+    let before: String = line.chars().take(col).collect();
+    let mut indent = get_line_indent(&before).to_string();
+    if let Some(last) = before.trim_end().chars().last() {
+        if language.indent_openers.contains(last) {
+            indent.push_str(input_area.indent());
+        }
+    }
+    indent
+}
+
+// Insert count newlines at the cursor as a single edit, each indented to match the line the cursor is on
+// (one level further after an opener). When the cursor is between an opener and its closer, the closer goes
+// onto its own line at the original indentation
+pub fn insert_newlines(input_area: &mut TextArea, count: usize, language: &LanguageStruct) {
+    let indent = get_newline_indent(input_area, language);
+    let (row, col) = input_area.cursor();
+    let line = &input_area.lines()[row];
+    let before: String = line.chars().take(col).collect();
+    let after: String = line.chars().skip(col).collect();
+    let is_opened = indent.len() > get_line_indent(&before).len();
+    let closes_block = after
+        .trim_start()
+        .chars()
+        .next()
+        .is_some_and(|first| get_indent_closers(language).contains(first));
+    input_area.insert_str(format!("\n{indent}").repeat(count.max(1)));
+    if is_opened && closes_block {
+        let (row, col) = input_area.cursor();
+        input_area.insert_str(format!("\n{}", get_line_indent(&before)));
+        jump_to(input_area, row, col);
+    }
+}
+
+// Take one level of indentation off the cursor's line when a closer is typed as the first thing on it
+pub fn dedent_closer(input_area: &mut TextArea, language: &LanguageStruct, typed: char) {
+    if !get_indent_closers(language).contains(typed) {
+        return;
+    }
+    let (row, col) = input_area.cursor();
+    let line = &input_area.lines()[row];
+    let before: String = line.chars().take(col).collect();
+    if before.is_empty() || !before.trim().is_empty() {
+        return;
+    }
+    // Take off a tab, or up to a tab length of spaces
+    let mut indent = before;
+    match indent.ends_with('\t') {
+        true => {
+            indent.pop();
+        }
+        false => {
+            for _ in 0..input_area.tab_length() {
+                if indent.ends_with(' ') {
+                    indent.pop();
+                }
+            }
+        }
+    }
+    let yank_text = input_area.yank_text();
+    input_area.delete_line_by_head();
+    input_area.set_yank_text(yank_text);
+    input_area.insert_str(indent);
+}
+
+// Setup the struct which holds how a file is indented
+pub struct IndentStyleStruct {
+    pub hard_tab: bool,
+    // The width of a level of indentation in columns
+    pub width: u8,
+}
+
+// Work out whether lines are indented with tabs or spaces, and how many spaces make a level
+// Returns None if no lines are indented. tab_length is used as the width when tabs are used
+pub fn detect_indent(lines: &[String], tab_length: u8) -> Option<IndentStyleStruct> {
+    let mut tab_lines = 0;
+    let mut space_lines = 0;
+    // How often each step in indentation (in spaces) appears between one line and the next
+    let mut steps = [0; 9];
+    let mut last_spaces = 0;
+    for line in lines.iter().filter(|line| !line.trim().is_empty()) {
+        let indent = get_line_indent(line);
+        if indent.starts_with('\t') {
+            tab_lines += 1;
+            continue;
+        }
+        let spaces = indent.len();
+        if spaces > 0 {
+            space_lines += 1;
+        }
+        if spaces > last_spaces && spaces - last_spaces < steps.len() {
+            steps[spaces - last_spaces] += 1;
+        }
+        last_spaces = spaces;
+    }
+    if tab_lines == 0 && space_lines == 0 {
+        return None;
+    }
+    if tab_lines > space_lines {
+        return Some(IndentStyleStruct {
+            hard_tab: true,
+            width: tab_length,
+        });
+    }
+    // A step of one space is more often lining something up than a level of indentation
+    let width = (2..steps.len())
+        .max_by_key(|&step| (steps[step], step == 4 || step == 2))
+        .filter(|&step| steps[step] > 0)
+        .unwrap_or(tab_length as usize);
+    Some(IndentStyleStruct {
+        hard_tab: false,
+        width: width as u8,
+    })
+}
+
+// Use the indentation style a file already has for new indentation, over the hardtab/tablength config
+// Returns the detected style, or None (keeping the config) if no lines are indented
+pub fn apply_detected_indent(input_area: &mut TextArea) -> Option<IndentStyleStruct> {
+    let style = detect_indent(input_area.lines(), input_area.tab_length())?;
+    input_area.set_hard_tab_indent(style.hard_tab);
+    input_area.set_tab_length(style.width);
+    Some(style)
+}
+
+// Describe the indentation used for new lines, for the status bar
+pub fn get_indent_name(input_area: &TextArea) -> String {
+    match input_area.hard_tab_indent() {
+        true => "Tabs".to_string(),
+        false => format!("Spaces: {}", input_area.tab_length()),
+    }
+}
+
+// Get the rows a line command works on: the selected rows if there is a selection, otherwise count rows from the cursor
+pub fn get_line_rows(input_area: &TextArea, count: usize) -> (usize, usize) {
+    match input_area.selection_range() {
+        Some(((start_row, _), (end_row, _))) => (start_row, end_row),
+        None => {
+            let row = input_area.cursor().0;
+            (
+                row,
+                (row + count.max(1) - 1).min(input_area.lines().len() - 1),
+            )
+        }
+    }
+}
+
+// Replace the whitespace at the start of a line, keeping the cursor on the same character
+fn set_line_indent(input_area: &mut TextArea, row: usize, indent: &str) {
+    let (cursor_row, cursor_col) = input_area.cursor();
+    let old_indent = get_line_indent(&input_area.lines()[row]).chars().count();
+    if get_line_indent(&input_area.lines()[row]) == indent {
+        return;
+    }
+    input_area.cancel_selection();
+    jump_to(input_area, row, 0);
+    input_area.start_selection();
+    jump_to(input_area, row, old_indent);
+    input_area.delete_str(0);
+    input_area.cancel_selection();
+    input_area.insert_str(indent);
+    let new_indent = indent.chars().count();
+    let cursor_col = match cursor_row == row {
+        true if cursor_col >= old_indent => cursor_col - old_indent + new_indent,
+        true => cursor_col.min(new_indent),
+        false => cursor_col,
+    };
+    jump_to(input_area, cursor_row, cursor_col);
+}
+
+// Add a level of indentation to the start of each line in a range of rows (empty lines are left empty)
+pub fn indent_lines(input_area: &mut TextArea, start_row: usize, end_row: usize) {
+    let unit = input_area.indent();
+    for row in start_row..=end_row {
+        let line = &input_area.lines()[row];
+        if line.is_empty() {
+            continue;
+        }
+        let indent = format!("{unit}{}", get_line_indent(line));
+        set_line_indent(input_area, row, &indent);
+    }
+}
+
+// Take a level of indentation (a tab, or up to a tab length of spaces) off each line in a range of rows
+pub fn dedent_lines(input_area: &mut TextArea, start_row: usize, end_row: usize) {
+    let tab_length = input_area.tab_length() as usize;
+    for row in start_row..=end_row {
+        let indent = get_line_indent(&input_area.lines()[row]);
+        let removed = match indent.starts_with('\t') {
+            true => 1,
+            false => indent.len() - indent.trim_start_matches(' ').len(),
+        };
+        let indent = indent[removed.min(tab_length.max(1))..].to_string();
+        set_line_indent(input_area, row, &indent);
+    }
+}
+
+// Rewrite the indentation of every line in the style the file mostly uses (e.g. lines indented with tabs in a file
+// indented with spaces), and use that style for new indentation. Returns false if the file has no indentation
+pub fn reindent_lines(input_area: &mut TextArea) -> bool {
+    // Tabs already in the file are measured with the tab length from before the style is applied
+    let tab_length = input_area.tab_length().max(1) as usize;
+    let style = match apply_detected_indent(input_area) {
+        Some(style) => style,
+        None => return false,
+    };
+    for row in 0..input_area.lines().len() {
+        // Measure the indentation in columns, with tabs going to the next tab stop
+        let mut columns = 0;
+        for c in get_line_indent(&input_area.lines()[row]).chars() {
+            columns = match c {
+                '\t' => (columns / tab_length + 1) * tab_length,
+                _ => columns + 1,
+            };
+        }
+        let width = style.width.max(1) as usize;
+        let indent = match style.hard_tab {
+            true => format!(
+                "{}{}",
+                "\t".repeat(columns / width),
+                " ".repeat(columns % width)
+            ),
+            false => " ".repeat(columns),
+        };
+        if !input_area.lines()[row].trim().is_empty() {
+            set_line_indent(input_area, row, &indent);
+        }
+    }
+    true
+}
@@ -0,0 +1,82 @@
+use std::{
+    sync::mpsc::{channel, Receiver, Sender},
+    thread,
+    time::Duration,
+};
+
+use crate::file_handler::get_file_modified;
+
+// How often the file being edited is checked for changes made outside the editor
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+// The kinds of job that send results back to the editor
+pub enum JobKind {
+    // The file was run through its formatter
+    Format,
+    // The file was changed on disk
+    FileChanged,
+}
+
+// Setup the struct which holds what a background job sends back to the editor
+pub struct JobResultStruct {
+    pub kind: JobKind,
+    // The command the job ran (e.g. the formatter), to say which one failed
+    pub command: String,
+    // The text the job made (e.g. the formatted file), None if it failed
+    pub output: Option<String>,
+    // A hash of the lines the job started from, so its output can be left out if they have changed since
+    pub content_hash: u64,
+}
+
+// Setup the struct which holds the channel background jobs send their results down
+pub struct JobsStruct {
+    sender: Sender<JobResultStruct>,
+    receiver: Receiver<JobResultStruct>,
+}
+
+pub fn new_jobs() -> JobsStruct {
+    let (sender, receiver) = channel();
+    JobsStruct { sender, receiver }
+}
+
+// Run a job on another thread, so the editor keeps taking input while it runs. Its result is sent back to the
+// editor once it finishes
+pub fn spawn_job(jobs: &JobsStruct, job: impl FnOnce() -> JobResultStruct + Send + 'static) {
+    let sender = jobs.sender.clone();
+    thread::spawn(move || {
+        // The editor may have closed before the job finished, which leaves nowhere to send its result
+        let _ = sender.send(job());
+    });
+}
+
+// Get the results of the jobs that have finished since this was last called
+pub fn take_job_results(jobs: &JobsStruct) -> Vec<JobResultStruct> {
+    jobs.receiver.try_iter().collect()
+}
+
+// Watch a file on another thread, sending a FileChanged result whenever the time it was last changed does
+pub fn watch_file(jobs: &JobsStruct, file_path: &str) {
+    let sender = jobs.sender.clone();
+    let file_path = file_path.to_string();
+    thread::spawn(move || {
+        let mut modified = get_file_modified(&file_path);
+        loop {
+            thread::sleep(WATCH_INTERVAL);
+            let new_modified = get_file_modified(&file_path);
+            if new_modified == modified {
+                continue;
+            }
+            modified = new_modified;
+            let result = JobResultStruct {
+                kind: JobKind::FileChanged,
+                command: String::new(),
+                output: None,
+                content_hash: 0,
+            };
+            // Stop watching once the editor has closed
+            if sender.send(result).is_err() {
+                break;
+            }
+        }
+    });
+}
@@ -0,0 +1,597 @@
+use std::{collections::HashMap, path::Path, sync::OnceLock};
+
+use configparser::ini::Ini;
+
+use crate::config_handler::{glob_matches_path, new_config_parser};
+
+// The languages the editor knows about, in the same format as the [lang.id] sections in the config file (which can
+// change any of these, or add new languages)
+// Keys that aren't about the language itself (e.g. hardtab, tablength, formatter, autopairs) are used as the
+// language's config options when the config doesn't set them
+const BUILTIN_LANGUAGES: &str = r#"
+[lang.text]
+name = Text File
+extensions = txt
+indentopeners =
+autopairs = false
+wrap = true
+
+# Configuration Files
+[lang.cfg]
+name = CFG File
+extensions = cfg
+linecomment = #
+quotes = "
+
+[lang.json]
+name = JSON File
+extensions = json
+quotes = "
+
+[lang.toml]
+name = TOML File
+extensions = toml
+filenames = Cargo.lock
+linecomment = #
+quotes = "'
+
+[lang.yaml]
+name = YAML File
+extensions = yaml yml
+linecomment = #
+quotes = "'
+indentopeners = :([{
+tablength = 2
+
+[lang.ini]
+name = INI File
+extensions = ini
+filenames = .editorconfig rcl_config.txt
+linecomment = ;
+quotes = "
+
+[lang.csv]
+name = Comma Separated Values File
+extensions = csv
+quotes = "
+indentopeners =
+
+# Git Specific Files
+[lang.git-ignore]
+name = Git Ignore File
+extensions = gitignore
+filenames = .gitignore
+globs = .git/info/exclude
+linecomment = #
+indentopeners =
+
+[lang.git-attributes]
+name = Git Attributes File
+extensions = gitattributes
+filenames = .gitattributes
+globs = .git/info/attributes
+linecomment = #
+indentopeners =
+
+[lang.git-commit]
+name = Git Commit Message
+filenames = COMMIT_EDITMSG MERGE_MSG TAG_EDITMSG
+linecomment = #
+indentopeners =
+autopairs = false
+textwidth = 72
+
+# UI/Markup Files
+[lang.markdown]
+name = Markdown File
+extensions = md markdown
+blockcomment = <!-- -->
+indentopeners =
+autopairs = false
+wrap = true
+
+[lang.xml]
+name = XML File
+extensions = xml
+blockcomment = <!-- -->
+quotes = "'
+
+[lang.xaml]
+name = XAML File
+extensions = xaml
+blockcomment = <!-- -->
+quotes = "'
+
+[lang.axaml]
+name = AXAML File
+extensions = axaml
+blockcomment = <!-- -->
+quotes = "'
+
+[lang.html]
+name = HTML File
+extensions = html htm
+blockcomment = <!-- -->
+quotes = "'
+
+[lang.xhtml]
+name = XHTML File
+extensions = xhtml
+blockcomment = <!-- -->
+quotes = "'
+
+[lang.css]
+name = CSS File
+extensions = css
+blockcomment = /* */
+quotes = "'
+
+# Script Files
+[lang.shell]
+name = Shell Script
+extensions = sh bash zsh
+filenames = .bashrc .bash_profile .profile .zshrc
+shebangs = sh bash zsh dash ksh
+linecomment = #
+quotes = "'`
+
+[lang.powershell]
+name = PowerShell Script
+extensions = ps1
+shebangs = pwsh
+linecomment = #
+blockcomment = <# #>
+quotes = "'
+
+[lang.batch]
+name = Batch File
+extensions = bat cmd
+linecomment = REM
+quotes = "
+
+# Build Files
+[lang.makefile]
+name = Makefile
+extensions = mk
+filenames = Makefile makefile GNUmakefile
+linecomment = #
+hardtab = true
+
+[lang.dockerfile]
+name = Dockerfile
+extensions = dockerfile
+filenames = Dockerfile Containerfile
+globs = Dockerfile.*
+linecomment = #
+quotes = "'
+
+# Programming Source Files
+[lang.c]
+name = C Source File
+extensions = c
+linecomment = //
+blockcomment = /* */
+quotes = "'
+
+[lang.c/c++]
+name = C/C++ Source File
+extensions = h
+linecomment = //
+blockcomment = /* */
+quotes = "'
+
+[lang.c++]
+name = C++ Source File
+extensions = cpp C cc cxx c++ H hh hpp hxx h++ cppm ixx
+linecomment = //
+blockcomment = /* */
+quotes = "'
+
+[lang.r]
+name = R Source File
+extensions = r R
+shebangs = Rscript
+linecomment = #
+quotes = "'
+
+[lang.scala]
+name = Scala Source File
+extensions = scala sc
+shebangs = scala
+linecomment = //
+blockcomment = /* */
+quotes = "
+
+[lang.ocaml]
+name = OCaml File
+extensions = ml mli
+shebangs = ocaml
+blockcomment = (* *)
+quotes = "
+
+[lang.ocamllex]
+name = OCamllex File
+extensions = mll
+blockcomment = (* *)
+quotes = "
+
+[lang.gdscript]
+name = GDScript Source File
+extensions = gd
+linecomment = #
+quotes = "'
+indentopeners = :([{
+
+# ' is left out of Rust's quotes, as it starts lifetimes as well as characters
+[lang.rust]
+name = Rust Source File
+extensions = rs
+linecomment = //
+blockcomment = /* */
+quotes = "
+formatter = rustfmt --edition 2021
+
+[lang.zig]
+name = Zig Source File
+extensions = zig
+linecomment = //
+quotes = "
+formatter = zig fmt --stdin
+
+[lang.c#]
+name = C# Source File
+extensions = cs
+linecomment = //
+blockcomment = /* */
+quotes = "
+
+[lang.f#]
+name = F# Source File
+extensions = fs fsi fsx
+linecomment = //
+blockcomment = (* *)
+quotes = "
+
+[lang.haskell]
+name = Haskell Source File
+extensions = hs
+shebangs = runhaskell
+linecomment = --
+blockcomment = {- -}
+quotes = "
+
+[lang.erlang]
+name = Erlang Source File
+extensions = erl
+shebangs = escript
+linecomment = %
+quotes = "
+
+[lang.python]
+name = Python Source File
+extensions = py pyw
+shebangs = python
+linecomment = #
+quotes = "'
+indentopeners = :([{
+
+[lang.java]
+name = Java Source File
+extensions = java
+linecomment = //
+blockcomment = /* */
+quotes = "
+
+[lang.go]
+name = Go Source File
+extensions = go
+linecomment = //
+blockcomment = /* */
+quotes = "`
+hardtab = true
+formatter = gofmt
+
+[lang.lua]
+name = Lua Source File
+extensions = lua
+shebangs = lua
+linecomment = --
+blockcomment = --[[ ]]
+quotes = "'
+
+[lang.crystal]
+name = Crystal Source File
+extensions = cr
+shebangs = crystal
+linecomment = #
+quotes = "
+
+[lang.haxe]
+name = Haxe Source File
+extensions = hx
+linecomment = //
+blockcomment = /* */
+quotes = "'
+
+[lang.swift]
+name = Swift Source File
+extensions = swift
+shebangs = swift
+linecomment = //
+blockcomment = /* */
+quotes = "
+
+[lang.dart]
+name = Dart Source File
+extensions = dart
+linecomment = //
+blockcomment = /* */
+quotes = "'
+
+[lang.perl]
+name = Perl Source File
+extensions = pl pm
+shebangs = perl
+linecomment = #
+quotes = "'
+
+[lang.ruby]
+name = Ruby Source File
+extensions = rb
+filenames = Gemfile Rakefile
+shebangs = ruby
+linecomment = #
+quotes = "'
+
+[lang.php]
+name = PHP Source File
+extensions = php
+shebangs = php
+linecomment = //
+blockcomment = /* */
+quotes = "'
+
+[lang.javascript]
+name = JavaScript Source File
+extensions = js mjs cjs
+shebangs = node
+linecomment = //
+blockcomment = /* */
+quotes = "'`
+
+[lang.typescript]
+name = TypeScript Source File
+extensions = ts mts cts
+shebangs = deno
+linecomment = //
+blockcomment = /* */
+quotes = "'`
+"#;
+
+// Setup the struct which holds what the editor knows about a language
+#[derive(Clone)]
+pub struct LanguageStruct {
+    // The name used for the language's [lang.id] config section
+    pub id: String,
+    // The name shown in the status bar
+    pub name: String,
+    pub extensions: Vec<String>,
+    pub file_names: Vec<String>,
+    // The interpreters that mean a file is in this language when they are on its #! line (python also matches python3)
+    pub shebangs: Vec<String>,
+    // Globs matched like [glob."pattern"] sections (against the file name, or the end of the path if they have a /)
+    pub globs: Vec<String>,
+    pub line_comment: Option<String>,
+    pub block_comment: Option<(String, String)>,
+    // The characters that start and end strings
+    pub quotes: String,
+    // The brackets that are matched, as (opener, closer)
+    pub brackets: Vec<(char, char)>,
+    // The characters that open an indented block when they end a line
+    pub indent_openers: String,
+    // Config options used for files in the language when the config doesn't set them (e.g. hardtab, formatter)
+    pub defaults: HashMap<String, String>,
+}
+
+// Make a language with no comments or strings, which is what files the registry doesn't know are treated as
+fn new_language(id: &str, name: &str) -> LanguageStruct {
+    LanguageStruct {
+        id: id.to_string(),
+        name: name.to_string(),
+        extensions: Vec::new(),
+        file_names: Vec::new(),
+        shebangs: Vec::new(),
+        globs: Vec::new(),
+        line_comment: None,
+        block_comment: None,
+        quotes: String::new(),
+        brackets: vec![('(', ')'), ('[', ']'), ('{', '}')],
+        indent_openers: "([{".to_string(),
+        defaults: HashMap::new(),
+    }
+}
+
+// Apply the keys from a [lang.id] section to a language. Keys that aren't about the language are kept as its
+// defaults if is_builtin is true (a user's [lang.id] section is already read by get_config_value)
+fn apply_language_section(
+    language: &mut LanguageStruct,
+    section: &HashMap<String, Option<String>>,
+    is_builtin: bool,
+) {
+    for (key, value) in section {
+        let value = value.clone().unwrap_or_default();
+        let words = || value.split_whitespace().map(String::from).collect();
+        match key.as_str() {
+            "name" => language.name = value.clone(),
+            "extensions" => language.extensions = words(),
+            "filenames" => language.file_names = words(),
+            "shebangs" => language.shebangs = words(),
+            "globs" => language.globs = words(),
+            "linecomment" => language.line_comment = Some(value.clone()).filter(|v| !v.is_empty()),
+            "blockcomment" => {
+                language.block_comment = match value.split_whitespace().collect::<Vec<_>>()[..] {
+                    [start, end] => Some((start.to_string(), end.to_string())),
+                    _ => None,
+                }
+            }
+            "quotes" => language.quotes = value.split_whitespace().collect(),
+            "brackets" => {
+                language.brackets = value
+                    .split_whitespace()
+                    .filter_map(|pair| match pair.chars().collect::<Vec<_>>()[..] {
+                        [opener, closer] => Some((opener, closer)),
+                        _ => None,
+                    })
+                    .collect()
+            }
+            "indentopeners" => language.indent_openers = value.split_whitespace().collect(),
+            _ if is_builtin => {
+                language.defaults.insert(key.clone(), value);
+            }
+            _ => {
+                // Pass
+            }
+        }
+    }
+}
+
+// Get the built in languages as config sections, which are only parsed the first time they are needed
+fn get_builtin_languages() -> &'static Ini {
+    static BUILTIN: OnceLock<Ini> = OnceLock::new();
+    BUILTIN.get_or_init(|| {
+        let mut builtin = new_config_parser();
+        builtin
+            .read(BUILTIN_LANGUAGES.to_string())
+            .expect("Failed to parse the built in languages");
+        builtin
+    })
+}
+
+// Get every language, from the built in ones and the [lang.id] sections in the config
+// Languages the config gives a way of being found (e.g. extensions) come first, so they are found over built in ones
+pub fn get_languages(config: &Ini) -> Vec<LanguageStruct> {
+    let builtin = get_builtin_languages();
+    let get_ids = |config: &Ini| -> Vec<String> {
+        let mut ids: Vec<String> = config
+            .sections()
+            .into_iter()
+            .filter_map(|section| section.strip_prefix("lang.").map(String::from))
+            .collect();
+        ids.sort();
+        ids
+    };
+    let user_sections = config.get_map_ref();
+    let is_found_by_user = |id: &String| {
+        user_sections
+            .get(&format!("lang.{id}"))
+            .is_some_and(|section| {
+                ["extensions", "filenames", "shebangs", "globs"]
+                    .iter()
+                    .any(|key| section.contains_key(*key))
+            })
+    };
+    let mut ids: Vec<String> = get_ids(config)
+        .into_iter()
+        .filter(|id| is_found_by_user(id))
+        .collect();
+    for id in get_ids(builtin) {
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    let mut languages = Vec::new();
+    for id in ids {
+        let mut language = new_language(&id, &id);
+        let section = format!("lang.{id}");
+        if let Some(section) = builtin.get_map_ref().get(&section) {
+            apply_language_section(&mut language, section, true);
+        }
+        if let Some(section) = user_sections.get(&section) {
+            apply_language_section(&mut language, section, false);
+        }
+        languages.push(language);
+    }
+    languages
+}
+
+// Get the name of the program a #! line runs, e.g. python3 for "#!/usr/bin/env python3"
+fn get_interpreter(first_line: &str) -> Option<&str> {
+    let mut words = first_line.strip_prefix("#!")?.split_whitespace();
+    let mut program = words.next()?;
+    program = program.rsplit('/').next().unwrap_or(program);
+    // env runs the program named after it (skipping its own options, e.g. -S)
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-'))?;
+        program = program.rsplit('/').next().unwrap_or(program);
+    }
+    Some(program)
+}
+
+// Work out the language of a file from its name, then globs, then its extension, then its #! line
+// Files that match none of these get a language with no comments or strings, named after their extension
+pub fn detect_language(config: &Ini, file_path: &String, first_line: &str) -> LanguageStruct {
+    let path = Path::new(file_path);
+    let file_name = path
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_string());
+    let languages = get_languages(config);
+    let interpreter = get_interpreter(first_line);
+    let finders: [&dyn Fn(&LanguageStruct) -> bool; 5] = [
+        &|language| language.file_names.contains(&file_name),
+        &|language| {
+            language
+                .globs
+                .iter()
+                .any(|glob| glob_matches_path(glob, file_path))
+        },
+        &|language| {
+            extension
+                .as_ref()
+                .is_some_and(|e| language.extensions.contains(e))
+        },
+        // Extensions that only differ in case are only used if none match exactly (e.g. .C is C++ but .c is C)
+        &|language| {
+            extension.as_ref().is_some_and(|e| {
+                language
+                    .extensions
+                    .iter()
+                    .any(|language_extension| language_extension.eq_ignore_ascii_case(e))
+            })
+        },
+        &|language| {
+            interpreter.is_some_and(|interpreter| {
+                language.shebangs.iter().any(|shebang| {
+                    // A version can come after the interpreter's name (e.g. python3.12)
+                    interpreter
+                        .strip_prefix(shebang.as_str())
+                        .is_some_and(|version| {
+                            version.chars().all(|c| c.is_ascii_digit() || c == '.')
+                        })
+                })
+            })
+        },
+    ];
+    for finder in finders {
+        if let Some(language) = languages.iter().find(|language| finder(language)) {
+            return language.clone();
+        }
+    }
+    match extension {
+        Some(extension) => new_language(&extension.to_lowercase(), &extension),
+        None => new_language("", "Unknown File"),
+    }
+}
+
+// Get the characters that close an indented block when they start a line, from the brackets whose openers open one
+pub fn get_indent_closers(language: &LanguageStruct) -> String {
+    language
+        .brackets
+        .iter()
+        .filter(|(opener, _)| language.indent_openers.contains(*opener))
+        .map(|&(_, closer)| closer)
+        .collect()
+}
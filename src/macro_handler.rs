@@ -0,0 +1,289 @@
+use std::{collections::HashMap, fs};
+
+use configparser::ini::Ini;
+use tui_textarea::{Input, Key};
+
+use crate::config_handler::get_config_dir;
+
+// The deepest a macro can replay other macros (stops a macro that replays itself from looping forever)
+const MAX_REPLAY_DEPTH: u8 = 16;
+
+// Setup the struct which will be used to hold the macro registers and the recording state
+pub struct MacroStruct {
+    pub registers: HashMap<char, Vec<Input>>,
+    pub recording: Option<char>,
+    pub recorded: Vec<Input>,
+    pub last_replayed: Option<char>,
+    pub replay_depth: u8,
+}
+
+// Get the path to the file the macros are saved in
+fn get_macro_path() -> std::path::PathBuf {
+    get_config_dir().join("rcl_macros.txt")
+}
+
+// Only letters can be used as macro registers
+pub fn is_register(register: char) -> bool {
+    register.is_ascii_alphabetic()
+}
+
+// Load the saved macros from the config directory (if there are any)
+pub fn load_macros() -> MacroStruct {
+    let mut registers = HashMap::new();
+    let mut macro_file = Ini::new_cs();
+    match macro_file.load(get_macro_path()) {
+        Ok(_) => {
+            if let Some(section) = macro_file.get_map_ref().get("macros") {
+                for (name, tokens) in section {
+                    let mut name_chars = name.chars();
+                    match (name_chars.next(), name_chars.next(), tokens) {
+                        (Some(register), None, Some(tokens)) if is_register(register) => {
+                            let inputs: Vec<Input> = tokens
+                                .split_whitespace()
+                                .filter_map(token_to_input)
+                                .collect();
+                            registers.insert(register, inputs);
+                        }
+                        _ => {
+                            // Pass
+                        }
+                    }
+                }
+            }
+        }
+        Err(_) => {
+            // Pass
+        }
+    }
+    MacroStruct {
+        registers,
+        recording: None,
+        recorded: Vec::new(),
+        last_replayed: None,
+        replay_depth: 0,
+    }
+}
+
+// Write every macro register to the macro file, so they can be used again after a restart
+pub fn save_macros(macros: &MacroStruct) {
+    let mut macro_file = Ini::new_cs();
+    for (register, inputs) in &macros.registers {
+        let tokens: Vec<String> = inputs.iter().filter_map(input_to_token).collect();
+        macro_file.set("macros", &register.to_string(), Some(tokens.join(" ")));
+    }
+    let _ = fs::create_dir_all(get_config_dir());
+    let _ = macro_file.write(get_macro_path());
+}
+
+pub fn start_recording(macros: &mut MacroStruct, register: char) {
+    macros.recording = Some(register);
+    macros.recorded.clear();
+}
+
+// Stop recording and store the recorded inputs in the register
+pub fn stop_recording(macros: &mut MacroStruct) {
+    if let Some(register) = macros.recording.take() {
+        // The last input is the key that stopped the recording, so it shouldn't be replayed
+        macros.recorded.pop();
+        let recorded = std::mem::take(&mut macros.recorded);
+        macros.registers.insert(register, recorded);
+        save_macros(macros);
+    }
+}
+
+// Add an input to the macro being recorded (inputs from replayed macros are left out)
+pub fn record_input(macros: &mut MacroStruct, input: &Input) {
+    match (macros.recording, &input.key) {
+        (Some(_), Key::Null) => {
+            // Pass
+        }
+        (Some(_), _) if macros.replay_depth == 0 => {
+            macros.recorded.push(input.clone());
+        }
+        _ => {
+            // Pass
+        }
+    }
+}
+
+// Get the inputs stored in a register, if the replay depth hasn't been reached
+pub fn get_replay(macros: &MacroStruct, register: char) -> Option<Vec<Input>> {
+    match macros.replay_depth < MAX_REPLAY_DEPTH {
+        true => macros.registers.get(&register).cloned(),
+        false => None,
+    }
+}
+
+// Convert an input into a token for the macro file, e.g. "C-A-w" for Ctrl + Alt + w
+fn input_to_token(input: &Input) -> Option<String> {
+    let key_name = match input.key {
+        Key::Char(' ') => "<Space>".to_string(),
+        Key::Char('<') => "<lt>".to_string(),
+        Key::Char('#') => "<Hash>".to_string(),
+        Key::Char(';') => "<Semicolon>".to_string(),
+        Key::Char(c) => c.to_string(),
+        Key::F(n) => format!("<F{n}>"),
+        Key::Backspace => "<Backspace>".to_string(),
+        Key::Enter => "<Enter>".to_string(),
+        Key::Left => "<Left>".to_string(),
+        Key::Right => "<Right>".to_string(),
+        Key::Up => "<Up>".to_string(),
+        Key::Down => "<Down>".to_string(),
+        Key::Tab => "<Tab>".to_string(),
+        Key::Delete => "<Delete>".to_string(),
+        Key::Home => "<Home>".to_string(),
+        Key::End => "<End>".to_string(),
+        Key::PageUp => "<PageUp>".to_string(),
+        Key::PageDown => "<PageDown>".to_string(),
+        Key::Esc => "<Esc>".to_string(),
+        _ => return None,
+    };
+    let mut token = String::new();
+    if input.ctrl {
+        token.push_str("C-");
+    }
+    if input.alt {
+        token.push_str("A-");
+    }
+    if input.shift {
+        token.push_str("S-");
+    }
+    token.push_str(&key_name);
+    Some(token)
+}
+
+// Convert a token from the macro file back into an input
+fn token_to_input(token: &str) -> Option<Input> {
+    let mut input = Input::default();
+    let mut key_name = token;
+    // Strip the modifiers, as long as there is still a key name left after them
+    loop {
+        match key_name.get(..2) {
+            Some("C-") if key_name.len() > 2 => input.ctrl = true,
+            Some("A-") if key_name.len() > 2 => input.alt = true,
+            Some("S-") if key_name.len() > 2 => input.shift = true,
+            _ => break,
+        }
+        key_name = &key_name[2..];
+    }
+    input.key = match key_name {
+        "<Space>" => Key::Char(' '),
+        "<lt>" => Key::Char('<'),
+        "<Hash>" => Key::Char('#'),
+        "<Semicolon>" => Key::Char(';'),
+        "<Backspace>" => Key::Backspace,
+        "<Enter>" => Key::Enter,
+        "<Left>" => Key::Left,
+        "<Right>" => Key::Right,
+        "<Up>" => Key::Up,
+        "<Down>" => Key::Down,
+        "<Tab>" => Key::Tab,
+        "<Delete>" => Key::Delete,
+        "<Home>" => Key::Home,
+        "<End>" => Key::End,
+        "<PageUp>" => Key::PageUp,
+        "<PageDown>" => Key::PageDown,
+        "<Esc>" => Key::Esc,
+        _ => {
+            let mut chars = key_name.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Key::Char(c),
+                _ => match key_name
+                    .strip_prefix("<F")
+                    .and_then(|n| n.strip_suffix('>'))
+                {
+                    Some(n) => Key::F(n.parse().ok()?),
+                    None => return None,
+                },
+            }
+        }
+    };
+    Some(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_input(key: Key, ctrl: bool, alt: bool, shift: bool) -> Input {
+        Input {
+            key,
+            ctrl,
+            alt,
+            shift,
+        }
+    }
+
+    fn get_inputs() -> Vec<Input> {
+        let mut inputs: Vec<Input> = "aZ0-=[]>\"'é€C"
+            .chars()
+            .map(|c| new_input(Key::Char(c), false, false, false))
+            .collect();
+        inputs.extend(
+            [' ', '<', '#', ';', '-', 'C', 'A', 'S']
+                .map(|c| new_input(Key::Char(c), true, false, false)),
+        );
+        inputs.extend([
+            new_input(Key::Char('w'), true, true, false),
+            new_input(Key::Char('E'), false, true, true),
+            new_input(Key::Backspace, true, true, true),
+            new_input(Key::F(12), false, false, true),
+        ]);
+        inputs.extend(
+            [
+                Key::Enter,
+                Key::Left,
+                Key::Right,
+                Key::Up,
+                Key::Down,
+                Key::Tab,
+                Key::Delete,
+                Key::Home,
+                Key::End,
+                Key::PageUp,
+                Key::PageDown,
+                Key::Esc,
+                Key::F(1),
+            ]
+            .map(|key| new_input(key, false, false, false)),
+        );
+        inputs
+    }
+
+    #[test]
+    fn tokens_round_trip() {
+        for input in get_inputs() {
+            let token = input_to_token(&input).unwrap();
+            assert!(!token.contains(char::is_whitespace), "{token}");
+            assert_eq!(token_to_input(&token), Some(input), "{token}");
+        }
+    }
+
+    #[test]
+    fn macro_file_round_trips() {
+        let inputs = get_inputs();
+        let tokens: Vec<String> = inputs.iter().filter_map(input_to_token).collect();
+        let mut macro_file = Ini::new_cs();
+        macro_file.set("macros", "q", Some(tokens.join(" ")));
+        let mut read_file = Ini::new_cs();
+        read_file.read(macro_file.writes()).unwrap();
+        let read_inputs: Vec<Input> = read_file
+            .get("macros", "q")
+            .unwrap()
+            .split_whitespace()
+            .filter_map(token_to_input)
+            .collect();
+        assert_eq!(read_inputs, inputs);
+    }
+
+    #[test]
+    fn unknown_tokens_are_left_out() {
+        assert_eq!(token_to_input("<Nope>"), None);
+        assert_eq!(token_to_input("<Fx>"), None);
+        assert_eq!(token_to_input("ab"), None);
+        assert_eq!(
+            input_to_token(&new_input(Key::Null, false, false, false)),
+            None
+        );
+    }
+}
@@ -1,63 +1,171 @@
 // Import from standard
-use std::{fs, path::Path};
+use std::{
+    io,
+    path::Path,
+    time::{Duration, Instant, SystemTime},
+};
 
 // Import from crates
 use color_eyre::{eyre::Ok, Result};
+use configparser::ini::Ini;
+use crossterm::{
+    event::{DisableFocusChange, EnableFocusChange, Event},
+    ExecutableCommand,
+};
 use ratatui::{
-    layout::{self, Rect},
+    layout::{Constraint, Layout, Rect},
     style::{Modifier, Style},
-    text::Text,
-    widgets::{self, Block, Borders, Paragraph},
-    DefaultTerminal,
+    widgets::{Block, Borders},
+    DefaultTerminal, Frame,
 };
 use tui_textarea::{CursorMove, Input, Key, TextArea};
 
 // Mod external files
+mod autosave_handler;
+mod bracket_handler;
 mod cli_handler;
+mod comment_handler;
 mod config_handler;
+mod edit_handler;
+mod editorconfig_handler;
 mod file_handler;
+mod format_handler;
+mod goto_handler;
+mod indent_handler;
+mod job_handler;
+mod language_handler;
+mod macro_handler;
+mod mark_handler;
+mod message_handler;
+mod operator_handler;
+mod pair_handler;
+mod prompt_handler;
+mod reflow_handler;
+mod repeat_handler;
+mod status_handler;
+mod undo_handler;
+mod wrap_handler;
 
 // Get functions from external files
-use cli_handler::{boot_arg, get_file_path};
-use config_handler::run_config;
-use file_handler::{convert_extension, get_file_size, save_file};
-
-// Setup the struct which will be used for the status bar
-struct StatusBarStruct<'a> {
-    status_area: Rect,
-    status_paragraph: Paragraph<'a>,
-    status_text: Text<'a>,
-    cursor_line: usize,
-    cursor_row: usize,
-    last_command: &'a str,
-    status_content: String,
-    cursor_seperator: &'a str,
-    seperator: &'a str,
-}
+use autosave_handler::{apply_autosave_config, is_autosave_due, new_autosave, AutosaveStruct};
+use bracket_handler::{
+    find_matching_bracket, new_brackets, render_matching_bracket, BracketStruct,
+};
+use cli_handler::{boot_arg, get_file_path, get_start_position};
+use comment_handler::{toggle_block_comment, toggle_line_comments};
+use config_handler::{
+    get_config_modified, get_config_value, get_config_warnings, parse_config, run_config,
+};
+use edit_handler::{delete_lines, jump_to, move_cursor_count, paste_count, repeat_delete};
+use editorconfig_handler::{apply_editorconfig_file, apply_editorconfig_indent, get_editorconfig};
+use file_handler::{
+    get_content_hash, get_file_modified, get_file_size, new_file_settings, prepare_save, read_file,
+    save_file, FileSettingsStruct,
+};
+use format_handler::{apply_formatted_text, format_text, run_formatter};
+use goto_handler::parse_goto;
+use indent_handler::{
+    apply_detected_indent, dedent_closer, dedent_lines, get_indent_name, get_line_rows,
+    indent_lines, insert_newlines, reindent_lines,
+};
+use job_handler::{
+    new_jobs, spawn_job, take_job_results, watch_file, JobKind, JobResultStruct, JobsStruct,
+};
+use language_handler::{detect_language, LanguageStruct};
+use macro_handler::{
+    get_replay, is_register, load_macros, record_input, start_recording, stop_recording,
+    MacroStruct,
+};
+use mark_handler::{
+    get_mark, is_mark_name, jump_back, jump_forward, load_marks, record_jump, save_marks, set_mark,
+    shift_marks, MarkStruct,
+};
+use message_handler::{
+    add_message, apply_message_config, expire_message, get_shown_message, message_log_input,
+    new_messages, open_message_log, render_message, render_message_log, MessageLevel,
+    MessageLogStruct, MessagesStruct,
+};
+use operator_handler::{
+    apply_operator, get_motion_range, get_object_range, operator_name, OperatorStruct,
+};
+use pair_handler::{delete_pair, insert_pair_char};
+use prompt_handler::{new_prompt, prompt_input, render_prompt, PromptKind, PromptStruct};
+use reflow_handler::{get_text_width, reflow_text};
+use repeat_handler::{finish_command, get_repeat, new_repeat, record_key, RepeatStruct};
+use status_handler::{
+    apply_status_config, get_git_branch, get_status_content, new_status_bar, render_status_bar,
+    StatusBarStruct,
+};
+use undo_handler::{
+    get_state_in_time, get_state_minutes_ago, go_to_state, load_history, move_history_browser,
+    open_history_browser, record_history, redo, render_history_browser, save_history,
+    take_line_changes, undo, HistoryBrowserStruct, HistoryStruct,
+};
+use wrap_handler::{apply_wrap_config, move_screen_lines, new_wrap, render_wrapped, WrapStruct};
+
+// How often the editor checks on things that change without any input (the config file, message timeouts and the
+// status bar) when no input comes in
+const TICK_INTERVAL: Duration = Duration::from_millis(250);
+// The largest count a command can be given, larger counts are cut down to it
+const MAX_COUNT: usize = 99_999;
 
-fn setup(
-    input_area: TextArea,
-    status_bar: StatusBarStruct,
+// Setup the struct which holds the state of the editor, so it can be passed to the input handling
+struct EditorStruct<'a> {
+    input_area: TextArea<'a>,
+    status_bar: StatusBarStruct<'a>,
     is_ovr_mode: bool,
-    editor_mode: &str,
+    editor_mode: &'a str,
     is_modified: bool,
     file_path: String,
     file_size: String,
-    file_type: &str,
-) -> Result<()> {
+    // The language the file is in, which is what is shown as its file type
+    language: LanguageStruct,
+    macros: MacroStruct,
+    // Set when a key that needs a register or mark after it (q, @, m, ') has been pressed
+    pending_prefix: Option<char>,
+    // The number typed before a command, which repeats it that many times
+    pending_count: usize,
+    // Set when an operator has been typed and is waiting for a motion or text object
+    pending_operator: Option<OperatorStruct>,
+    repeat: RepeatStruct,
+    // Set while a prompt (e.g. go to line) is open in place of the status bar
+    prompt: Option<PromptStruct<'a>>,
+    marks: MarkStruct,
+    history: HistoryStruct,
+    // Set while the undo tree popup is open
+    history_browser: Option<HistoryBrowserStruct>,
+    // How the file is read and saved (newlines, charset, whitespace), from the file and its .editorconfig
+    file_settings: FileSettingsStruct,
+    // The command the file is run through to format it, from the config
+    formatter: Option<String>,
+    // Whether brackets and quotes typed in insert mode are closed automatically
+    auto_pairs: bool,
+    // The width Alt + q reflows text to
+    text_width: usize,
+    // When the config file was last changed, so changes to it can be applied while the editor is open
+    config_modified: Option<SystemTime>,
+    // The messages shown on the message line (e.g. a config error), and kept in the message log
+    messages: MessagesStruct,
+    // Set while the message log popup is open
+    message_log: Option<MessageLogStruct>,
+    // Whether long lines are wrapped onto the next screen line, and how
+    wrap: WrapStruct,
+    // What is kept between draws to find and draw the bracket matching the one under the cursor
+    brackets: BracketStruct,
+    // The channel background jobs (e.g. the formatter) send their results down
+    jobs: JobsStruct,
+    // When the file was last changed, to tell when it is changed outside the editor
+    file_modified: Option<SystemTime>,
+    autosave: AutosaveStruct,
+}
+
+fn setup(editor: EditorStruct) -> Result<()> {
     color_eyre::install()?;
     let terminal = ratatui::init();
-    let result = run(
-        terminal,
-        input_area,
-        status_bar,
-        is_ovr_mode,
-        editor_mode,
-        is_modified,
-        file_path,
-        file_size,
-        file_type,
-    );
+    // Focus changes are reported so the file can be autosaved when the terminal loses focus
+    let _ = io::stdout().execute(EnableFocusChange);
+    let result = run(terminal, editor);
+    let _ = io::stdout().execute(DisableFocusChange);
     ratatui::restore();
     result
 }
@@ -71,24 +179,11 @@ fn main() {
     // Set editor mode variables
     let is_ovr_mode = true;
     let editor_mode = "Ovr";
-    // Get file path, file size and file type
+    // Get file path and file size
     let file_path = get_file_path();
     let file_size = file_handler::get_file_size(&file_path);
-    let mut file_type: &str = Path::new(&file_path).extension().unwrap().to_str().unwrap();
-    // Convert file extension if applicable
-    file_type = convert_extension(file_type);
     // Initialise StatusBarStruct
-    let status_bar = StatusBarStruct {
-        status_area: Rect::new(0, 0, 0, 0),
-        status_paragraph: widgets::Paragraph::new("").alignment(ratatui::layout::Alignment::Left),
-        status_text: "".into(),
-        cursor_line: std::usize::MIN,
-        cursor_row: std::usize::MIN,
-        last_command: "",
-        status_content: "".into(),
-        cursor_seperator: ":",
-        seperator: " | ",
-    };
+    let mut status_bar = new_status_bar();
 
     // Declare input_area and it's block/styling
     let mut input_area: TextArea = TextArea::default();
@@ -99,439 +194,1575 @@ fn main() {
             .border_type(ratatui::widgets::BorderType::Rounded),
     );
 
+    let (config, config_error) = parse_config();
+
+    // Get the .editorconfig properties for the file, which can say how the file is read
+    let editorconfig = get_editorconfig(&file_path);
+    let mut file_settings = new_file_settings();
+    apply_editorconfig_file(&editorconfig, &mut file_settings);
+
     // Get contents from file and add them to the input_area
-    let file_contents = fs::read_to_string(&file_path);
-    input_area.insert_str(file_contents.expect("Failed to unwrap file contents"));
+    let file_text = read_file(&file_path, &mut file_settings);
+    // Work out the file's language from its name, or the #! line at the start of it
+    let language = detect_language(&config, &file_path, file_text.lines().next().unwrap_or(""));
+    input_area.insert_str(file_text);
 
     // Declare a bool that will be true when input_area.input(input); is called (see the input events below)
     // And be false after saving (except when saving and quitting)
     let is_modified = false;
 
     // Apply user config
-    run_config(&mut input_area);
+    run_config(&mut input_area, &config, &file_path, &language);
+    // Indent new lines the way the file is already indented, over the hardtab/tablength config
+    apply_detected_indent(&mut input_area);
+    // Indentation set in .editorconfig is used over both
+    apply_editorconfig_indent(&editorconfig, &mut input_area);
+
+    // Jump to the position added to the end of the file path (if there is one)
+    if let Some(position) = get_start_position() {
+        if let Some((row, col)) = parse_goto(&position, input_area.lines(), input_area.cursor()) {
+            jump_to(&mut input_area, row, col);
+        }
+    }
+
+    // Undo history is kept by the editor (so it can be saved between sessions), instead of by input_area
+    input_area.set_max_histories(0);
+    let history = load_history(&file_path, input_area.lines());
+
+    let formatter = get_config_value(&config, "formatter", &file_path, &language);
+    let auto_pairs = get_auto_pairs(&config, &file_path, &language);
+    let text_width = get_text_width(&config, &file_path, &language, &file_settings);
+    let mut wrap = new_wrap();
+    apply_wrap_config(&mut wrap, &config, &file_path, &language);
+    apply_status_config(&mut status_bar, &config, &file_path, &language);
+    let mut autosave = new_autosave(get_content_hash(input_area.lines()));
+    apply_autosave_config(&mut autosave, &config, &file_path, &language);
+    let jobs = new_jobs();
+    watch_file(&jobs, &file_path);
+    let mut messages = new_messages();
+    apply_message_config(&mut messages, &config, &file_path, &language);
+    add_config_messages(&mut messages, &config, config_error);
 
     // Continue to setup()
-    let _ = setup(
+    let editor = EditorStruct {
         input_area,
         status_bar,
         is_ovr_mode,
         editor_mode,
         is_modified,
-        file_path.clone(),
+        file_path: file_path.clone(),
         file_size,
-        file_type,
-    );
+        language,
+        macros: load_macros(),
+        pending_prefix: None,
+        pending_count: 0,
+        pending_operator: None,
+        repeat: new_repeat(),
+        prompt: None,
+        marks: load_marks(&file_path),
+        history,
+        history_browser: None,
+        file_settings,
+        formatter,
+        auto_pairs,
+        text_width,
+        config_modified: get_config_modified(),
+        messages,
+        message_log: None,
+        wrap,
+        brackets: new_brackets(),
+        jobs,
+        file_modified: get_file_modified(&file_path),
+        autosave,
+    };
+    let _ = setup(editor);
 }
 
-fn run(
-    mut terminal: DefaultTerminal,
-    mut input_area: TextArea,
-    mut status_bar: StatusBarStruct,
-    mut is_ovr_mode: bool,
-    mut editor_mode: &str,
-    mut is_modified: bool,
-    file_path: String,
-    mut file_size: String,
-    file_type: &str,
-) -> Result<()> {
+fn run(mut terminal: DefaultTerminal, mut editor: EditorStruct) -> Result<()> {
+    refresh_branch(&mut editor);
+    update_status_counts(&mut editor);
+    update_status_bar(&mut editor);
+    // Whether anything shown has changed since the last draw, so the screen is only drawn when it needs to be
+    let mut needs_redraw = true;
+    let mut last_tick = Instant::now();
     // Main loop to draw widgets and handle key inputs
     loop {
-        terminal.draw(|frame| {
-            frame.render_widget(&input_area, frame.area());
-            status_bar.status_area = Rect::new(0, frame.area().bottom(), 1000, 1);
-            frame.render_widget(
-                &status_bar.status_paragraph,
-                status_bar.status_area.clamp(frame.area()),
+        if needs_redraw {
+            terminal.draw(|frame| render(frame, &mut editor))?;
+            needs_redraw = false;
+        }
+        // Wait for input until the next tick. Every input that is already waiting is handled before drawing again,
+        // so typing (or pasting) quickly isn't slowed down by drawing after each key
+        if crossterm::event::poll(TICK_INTERVAL.saturating_sub(last_tick.elapsed()))? {
+            loop {
+                if handle_event(&mut terminal, &mut editor, crossterm::event::read()?)? {
+                    // Saving on the way out can change lines too
+                    shift_marks(&mut editor.marks, &take_line_changes(&mut editor.history));
+                    save_marks(&editor.marks, &editor.file_path);
+                    return Ok(());
+                }
+                if !crossterm::event::poll(Duration::ZERO)? {
+                    break;
+                }
+            }
+            needs_redraw = true;
+        }
+        if apply_job_results(&mut editor) {
+            needs_redraw = true;
+        }
+        if last_tick.elapsed() >= TICK_INTERVAL {
+            last_tick = Instant::now();
+            if get_config_modified() != editor.config_modified {
+                reload_config(&mut editor);
+                needs_redraw = true;
+            }
+            if is_autosave_due(&editor.autosave) && autosave_file(&mut editor) {
+                needs_redraw = true;
+            }
+            if expire_message(&mut editor.messages) {
+                needs_redraw = true;
+            }
+        }
+        // The file can only have changed when something has happened, so it isn't counted again on every tick
+        if needs_redraw {
+            update_status_counts(&mut editor);
+        }
+        // Segments such as the clock can change without any input
+        if update_status_bar(&mut editor) {
+            needs_redraw = true;
+        }
+    }
+}
+
+// Handle an event from the terminal, returns true if the program should exit
+fn handle_event(
+    terminal: &mut DefaultTerminal,
+    editor: &mut EditorStruct,
+    event: Event,
+) -> Result<bool> {
+    match event {
+        // The terminal is cleared and drawn again at its new size straight away, rather than on the next input, so
+        // nothing is left where the old size put it
+        Event::Resize(width, height) => {
+            terminal.resize(Rect::new(0, 0, width, height))?;
+            Ok(false)
+        }
+        Event::Key(_) | Event::Mouse(_) => {
+            editor.autosave.last_input = Instant::now();
+            Ok(handle_input(editor, event.into()))
+        }
+        Event::FocusGained => {
+            refresh_branch(editor);
+            Ok(false)
+        }
+        Event::FocusLost => {
+            if editor.autosave.is_on && editor.autosave.is_on_focus_lost {
+                autosave_file(editor);
+            }
+            Ok(false)
+        }
+        _ => Ok(false),
+    }
+}
+
+// Use the results of background jobs that have finished, returns true if there were any
+fn apply_job_results(editor: &mut EditorStruct) -> bool {
+    let results = take_job_results(&editor.jobs);
+    for result in &results {
+        match result.kind {
+            JobKind::Format => apply_format_result(editor, result),
+            JobKind::FileChanged => {
+                // Saving the file changes it too, which doesn't need a warning
+                let modified = get_file_modified(&editor.file_path);
+                refresh_branch(editor);
+                if modified != editor.file_modified {
+                    editor.file_modified = modified;
+                    add_message(
+                        &mut editor.messages,
+                        MessageLevel::Warning,
+                        format!("{} was changed outside the editor", editor.file_path),
+                    );
+                }
+            }
+        }
+    }
+    !results.is_empty()
+}
+
+// Replace the file's text with the formatter's output, unless it has been changed since the formatter started
+fn apply_format_result(editor: &mut EditorStruct, result: &JobResultStruct) {
+    let formatted = match &result.output {
+        Some(formatted) => formatted,
+        None => {
+            add_message(
+                &mut editor.messages,
+                MessageLevel::Error,
+                format!("Formatter failed: {}", result.command),
             );
-        })?;
-        // Get key input(s) and run appropriate functions for said input, or input it to the text area
-        match is_ovr_mode {
-            false => {
-                match crossterm::event::read()?.into() {
-                    Input { key: Key::Esc, .. } => {
-                        is_ovr_mode = true;
-                        editor_mode = "Ovr";
-                        input_area
-                            .set_cursor_style(Style::default().bg(ratatui::style::Color::Reset));
-                        input_area.set_cursor_style(
-                            Style::default()
-                                .fg(ratatui::style::Color::Reset)
-                                .add_modifier(Modifier::REVERSED),
-                        );
-                    }
-                    Input {
-                        key: Key::Char('a'),
-                        ctrl: true,
-                        ..
-                    } => {
-                        input_area.select_all();
+            editor.status_bar.last_command = "| FORMAT-FAILED";
+            return;
+        }
+    };
+    if get_content_hash(editor.input_area.lines()) != result.content_hash {
+        add_message(
+            &mut editor.messages,
+            MessageLevel::Warning,
+            "The file was changed while it was being formatted, so the formatting wasn't used"
+                .to_string(),
+        );
+        editor.status_bar.last_command = "| FORMAT-SKIPPED";
+        return;
+    }
+    apply_formatted_text(&mut editor.input_area, formatted.clone());
+    if record_history(&mut editor.history, &editor.input_area) {
+        editor.is_modified = true;
+    }
+    editor.status_bar.last_command = "| FORMAT";
+}
+
+// Draw the editor: the text area, the message line (while there is a message), the status bar (or a prompt in its
+// place), and any popup that is open over them
+fn render(frame: &mut Frame, editor: &mut EditorStruct) {
+    let message_height = match get_shown_message(&editor.messages) {
+        Some(_) => 1,
+        None => 0,
+    };
+    let [text_area, message_area, status_area] = Layout::vertical([
+        Constraint::Min(0),
+        Constraint::Length(message_height),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    match editor.wrap.is_on {
+        true => {
+            let highlight = find_matching_bracket(
+                &mut editor.brackets,
+                editor.input_area.lines(),
+                editor.input_area.cursor(),
+                &editor.language,
+            );
+            render_wrapped(
+                frame,
+                text_area,
+                &mut editor.input_area,
+                &mut editor.wrap,
+                highlight,
+            );
+        }
+        false => {
+            frame.render_widget(&editor.input_area, text_area);
+            render_matching_bracket(
+                frame,
+                text_area,
+                &mut editor.input_area,
+                &mut editor.brackets,
+                &editor.language,
+            );
+        }
+    }
+    render_message(frame, message_area, &editor.messages);
+    match &editor.prompt {
+        Some(prompt) => render_prompt(frame, status_area, prompt),
+        None => render_status_bar(frame, status_area, &editor.status_bar),
+    }
+    if let Some(browser) = &editor.history_browser {
+        render_history_browser(frame, frame.area(), &editor.history, browser);
+    }
+    if let Some(log) = &editor.message_log {
+        render_message_log(frame, frame.area(), &editor.messages, log);
+    }
+}
+
+// Handle a single input, returns true if the program should exit
+fn handle_input(editor: &mut EditorStruct, input: Input) -> bool {
+    // Add the input to the macro being recorded (if there is one)
+    record_input(&mut editor.macros, &input);
+    // Add the input to the command being typed, and keep the command once it is finished if it was a change
+    record_key(&mut editor.repeat, &input);
+    // Move the marks with any lines changed since the last input (e.g. by the formatter)
+    shift_marks(&mut editor.marks, &take_line_changes(&mut editor.history));
+    let was_modified = editor.is_modified;
+    let should_exit = dispatch_input(editor, input);
+    let is_pending = editor.pending_prefix.is_some()
+        || editor.pending_operator.is_some()
+        || editor.pending_count > 0;
+    // Add a finished command to the undo history if it changed the text (an insert mode session is one edit). Only
+    // commands that edit are looked at, since finding what changed compares every line
+    let is_edit = editor.repeat.is_change || editor.is_modified != was_modified;
+    if editor.is_ovr_mode
+        && !is_pending
+        && !editor.repeat.is_repeating
+        && is_edit
+        && record_history(&mut editor.history, &editor.input_area)
+    {
+        editor.is_modified = true;
+    }
+    shift_marks(&mut editor.marks, &take_line_changes(&mut editor.history));
+    // Keep the position of the last edit, which is already where it is after the lines changed
+    if editor.repeat.is_change {
+        set_mark(&mut editor.marks, '.', editor.input_area.cursor());
+    }
+    finish_command(&mut editor.repeat, editor.is_ovr_mode, is_pending);
+    should_exit
+}
+
+// Run the command for an input, returns true if the program should exit
+fn dispatch_input(editor: &mut EditorStruct, input: Input) -> bool {
+    // Send the input to the prompt if one is open, and run its command once Enter is pressed
+    if editor.prompt.is_some() {
+        let kind = editor.prompt.as_ref().unwrap().kind;
+        if let Some(text) = prompt_input(&mut editor.prompt, input) {
+            run_prompt(editor, kind, &text);
+        }
+        return false;
+    }
+    // Send the input to the message log popup if it is open
+    if let Some(log) = &mut editor.message_log {
+        if !message_log_input(log, &editor.messages, input) {
+            editor.message_log = None;
+        }
+        return false;
+    }
+    // Send the input to the undo tree popup if it is open
+    if editor.history_browser.is_some() {
+        handle_history_browser(editor, input);
+        return false;
+    }
+    // Finish a command that is waiting for a register
+    if let Some(prefix) = editor.pending_prefix.take() {
+        return handle_prefix(editor, prefix, input);
+    }
+    // Finish an operator that is waiting for a motion or text object
+    if let Some(operator) = editor.pending_operator.take() {
+        handle_operator(editor, operator, input);
+        return false;
+    }
+    // Counts are only kept for the key straight after them
+    let typed_count = editor.pending_count;
+    let count = typed_count.max(1);
+    if !matches!(input.key, Key::Char('0'..='9' | '@')) {
+        editor.pending_count = 0;
+    }
+    match editor.is_ovr_mode {
+        false => {
+            match input {
+                Input { key: Key::Esc, .. } => {
+                    editor.is_ovr_mode = true;
+                    editor.editor_mode = "Ovr";
+                    editor
+                        .input_area
+                        .set_cursor_style(Style::default().bg(ratatui::style::Color::Reset));
+                    editor.input_area.set_cursor_style(
+                        Style::default()
+                            .fg(ratatui::style::Color::Reset)
+                            .add_modifier(Modifier::REVERSED),
+                    );
+                }
+                Input {
+                    key: Key::Char('a'),
+                    ctrl: true,
+                    ..
+                } => {
+                    editor.input_area.select_all();
+                }
+                // Start the new line at the right indentation
+                Input {
+                    key: Key::Enter,
+                    ctrl: false,
+                    alt: false,
+                    ..
+                } => {
+                    insert_newlines(&mut editor.input_area, 1, &editor.language);
+                    editor.is_modified = true;
+                }
+                // Closing a block dedents the line it starts
+                Input {
+                    key: Key::Char(typed),
+                    ctrl: false,
+                    alt: false,
+                    ..
+                } => {
+                    dedent_closer(&mut editor.input_area, &editor.language, typed);
+                    // Close brackets and quotes, or step past a closer that is already there
+                    if !(editor.auto_pairs
+                        && insert_pair_char(&mut editor.input_area, typed, &editor.language))
+                    {
+                        editor.input_area.insert_char(typed);
                     }
-                    input => {
-                        // Add input to input_area
-                        input_area.input(input);
-                        // Change is_modified to true, in case a change was made to the input_area
-                        is_modified = true;
+                    editor.is_modified = true;
+                }
+                // Backspace between an empty pair deletes both
+                Input {
+                    key: Key::Backspace,
+                    ctrl: false,
+                    alt: false,
+                    ..
+                } => {
+                    if !(editor.auto_pairs && delete_pair(&mut editor.input_area, &editor.language))
+                    {
+                        editor.input_area.delete_char();
                     }
+                    editor.is_modified = true;
+                }
+                input => {
+                    // Add input to input_area
+                    editor.input_area.input(input);
+                    // Change is_modified to true, in case a change was made to the input_area
+                    editor.is_modified = true;
                 }
             }
-            true => {
-                match crossterm::event::read()?.into() {
-                    // Exit program, either via end key or Ctrl + Alt + Backspace
-                    Input { key: Key::End, .. } => break Ok(()),
-                    Input {
-                        key: Key::Backspace,
-                        ctrl: true,
-                        alt: true,
-                        ..
-                    } => break Ok(()),
-                    // Go to insert mode
-                    Input {
-                        key: Key::Char('i'),
-                        ..
-                    } => {
-                        is_ovr_mode = false;
-                        editor_mode = "Ins";
-                        input_area.set_cursor_style(
-                            Style::default().bg(ratatui::style::Color::LightCyan),
-                        );
-                        input_area.set_cursor_style(
-                            Style::default()
-                                .fg(ratatui::style::Color::LightCyan)
-                                .add_modifier(Modifier::REVERSED),
-                        );
-                    }
-                    // Save file
-                    Input {
-                        key: Key::Char('s'),
-                        ctrl: true,
-                        alt: false,
-                        ..
-                    } => {
-                        save_file(&is_modified, &file_path, &mut input_area);
-                        file_size = get_file_size(&file_path);
-                        is_modified = false;
-                    }
-                    // Save file and exit
-                    Input {
-                        key: Key::Char('s'),
-                        ctrl: true,
-                        alt: true,
-                        ..
-                    } => {
-                        save_file(&is_modified, &file_path, &mut input_area);
-                        break Ok(());
-                    }
-                    // General movement (hjkl, arrow keys)
-                    Input {
-                        key: Key::Char('h'),
-                        ..
-                    } => {
-                        input_area.cancel_selection();
-                        input_area.move_cursor(CursorMove::Back);
-                        status_bar.last_command = "| h";
-                    }
-                    Input {
-                        key: Key::Char('j'),
-                        ctrl: false,
-                        alt: false,
-                        ..
-                    } => {
-                        input_area.cancel_selection();
-                        input_area.move_cursor(CursorMove::Down);
-                        status_bar.last_command = "| j";
-                    }
-                    Input {
-                        key: Key::Char('k'),
-                        ..
-                    } => {
-                        input_area.cancel_selection();
-                        input_area.move_cursor(CursorMove::Up);
-                        status_bar.last_command = "| k";
-                    }
-                    Input {
-                        key: Key::Char('l'),
-                        ctrl: false,
-                        alt: false,
-                        ..
-                    } => {
-                        input_area.cancel_selection();
-                        input_area.move_cursor(CursorMove::Forward);
-                        status_bar.last_command = "| l";
-                    }
-                    Input { key: Key::Left, .. } => {
-                        input_area.cancel_selection();
-                        input_area.move_cursor(CursorMove::Back);
-                    }
-                    Input { key: Key::Down, .. } => {
-                        input_area.cancel_selection();
-                        input_area.move_cursor(CursorMove::Down);
-                    }
-                    Input { key: Key::Up, .. } => {
-                        input_area.cancel_selection();
-                        input_area.move_cursor(CursorMove::Up);
-                    }
-                    Input {
-                        key: Key::Right, ..
-                    } => {
-                        input_area.cancel_selection();
-                        input_area.move_cursor(CursorMove::Forward);
-                    }
-                    Input {
-                        key: Key::Char(' '),
-                        ..
-                    } => {
-                        input_area.cancel_selection();
-                        input_area.move_cursor(CursorMove::Forward);
-                        status_bar.last_command = "| >";
-                    }
-                    Input {
-                        key: Key::Backspace,
-                        ..
-                    } => {
-                        input_area.cancel_selection();
-                        input_area.move_cursor(CursorMove::Back);
-                        status_bar.last_command = "| <";
-                    }
-                    // Delete char
-                    Input {
-                        key: Key::Char('c'),
-                        ctrl: true,
-                        alt: true,
-                        ..
-                    } => {
-                        input_area.delete_next_char();
-                        status_bar.last_command = "| DEL-CHAR";
-                    }
-                    // Move around by word
-                    Input {
-                        key: Key::Char('w'),
-                        ctrl: true,
-                        alt: false,
-                        ..
-                    } => {
-                        input_area.move_cursor(CursorMove::WordForward);
-                        status_bar.last_command = "| WORD-FOR";
-                    }
-                    Input {
-                        key: Key::Char('w'),
-                        ctrl: false,
-                        alt: true,
-                        ..
-                    } => {
-                        input_area.move_cursor(CursorMove::WordBack);
-                        status_bar.last_command = "| WORD-BACK";
-                    }
-                    // Delete word (forward)
-                    Input {
-                        key: Key::Char('w'),
-                        ctrl: true,
-                        alt: true,
-                        ..
-                    } => {
-                        input_area.delete_next_word();
-                        status_bar.last_command = "| DEL-WORD";
-                    }
-                    // Move around by line
-                    Input {
-                        key: Key::Char('l'),
-                        ctrl: true,
-                        alt: false,
-                        ..
-                    } => {
-                        input_area.move_cursor(CursorMove::Down);
-                        status_bar.last_command = "| LINE-FOR";
+        }
+        true => {
+            match input {
+                // Exit program, either via end key or Ctrl + Alt + Backspace
+                Input { key: Key::End, .. } => return true,
+                Input {
+                    key: Key::Backspace,
+                    ctrl: true,
+                    alt: true,
+                    ..
+                } => return true,
+                // Go to insert mode
+                Input {
+                    key: Key::Char('i'),
+                    ..
+                } => {
+                    enter_insert_mode(editor);
+                }
+                // Save file
+                Input {
+                    key: Key::Char('s'),
+                    ctrl: true,
+                    alt: false,
+                    ..
+                } => {
+                    save_current_file(editor, false);
+                }
+                // Save file and exit
+                Input {
+                    key: Key::Char('s'),
+                    ctrl: true,
+                    alt: true,
+                    ..
+                } => {
+                    // The editor stays open if the file couldn't be saved, so the changes aren't lost
+                    if save_current_file(editor, false) {
+                        return true;
                     }
-                    Input {
-                        key: Key::Char('l'),
-                        ctrl: false,
-                        alt: true,
-                        ..
-                    } => {
-                        input_area.move_cursor(CursorMove::Up);
-                        status_bar.last_command = "| LINE-BACK";
+                }
+                // General movement (hjkl, arrow keys)
+                Input {
+                    key: Key::Char('h'),
+                    ..
+                } => {
+                    editor.input_area.cancel_selection();
+                    move_cursor_count(&mut editor.input_area, CursorMove::Back, count);
+                    editor.status_bar.last_command = "| h";
+                }
+                Input {
+                    key: Key::Char('j'),
+                    ctrl: false,
+                    alt: false,
+                    ..
+                } => {
+                    editor.input_area.cancel_selection();
+                    if !move_screen_lines(&mut editor.input_area, &editor.wrap, count, true) {
+                        move_cursor_count(&mut editor.input_area, CursorMove::Down, count);
                     }
-                    // Delete line
-                    Input {
-                        key: Key::Char('l'),
-                        ctrl: true,
-                        alt: true,
-                        ..
-                    } => {
-                        input_area.move_cursor(CursorMove::Head);
-                        input_area.delete_line_by_end();
-                        status_bar.last_command = "| DEL-LINE";
+                    editor.status_bar.last_command = "| j";
+                }
+                Input {
+                    key: Key::Char('k'),
+                    ..
+                } => {
+                    editor.input_area.cancel_selection();
+                    if !move_screen_lines(&mut editor.input_area, &editor.wrap, count, false) {
+                        move_cursor_count(&mut editor.input_area, CursorMove::Up, count);
                     }
-                    // Make a newline
-                    Input {
-                        key: Key::Char('n'),
-                        ctrl: true,
-                        alt: false,
-                        ..
-                    } => {
-                        input_area.move_cursor(CursorMove::Up);
-                        input_area.move_cursor(CursorMove::End);
-                        input_area.insert_newline();
-                        status_bar.last_command = "| NEW-LINE-UP";
+                    editor.status_bar.last_command = "| k";
+                }
+                Input {
+                    key: Key::Char('l'),
+                    ctrl: false,
+                    alt: false,
+                    ..
+                } => {
+                    editor.input_area.cancel_selection();
+                    move_cursor_count(&mut editor.input_area, CursorMove::Forward, count);
+                    editor.status_bar.last_command = "| l";
+                }
+                Input { key: Key::Left, .. } => {
+                    editor.input_area.cancel_selection();
+                    move_cursor_count(&mut editor.input_area, CursorMove::Back, count);
+                }
+                Input { key: Key::Down, .. } => {
+                    editor.input_area.cancel_selection();
+                    if !move_screen_lines(&mut editor.input_area, &editor.wrap, count, true) {
+                        move_cursor_count(&mut editor.input_area, CursorMove::Down, count);
                     }
-                    Input {
-                        key: Key::Char('n'),
-                        ctrl: false,
-                        alt: true,
-                        ..
-                    } => {
-                        input_area.move_cursor(CursorMove::End);
-                        input_area.insert_newline();
-                        status_bar.last_command = "| NEW-LINE-DOWN";
+                }
+                Input { key: Key::Up, .. } => {
+                    editor.input_area.cancel_selection();
+                    if !move_screen_lines(&mut editor.input_area, &editor.wrap, count, false) {
+                        move_cursor_count(&mut editor.input_area, CursorMove::Up, count);
                     }
-                    // Jump to start/end of line
-                    Input {
-                        key: Key::Char('e'),
-                        ctrl: true,
-                        alt: false,
-                        shift: false,
-                    } => {
-                        input_area.move_cursor(CursorMove::Head);
-                        status_bar.last_command = "| JUMP-LINE-START";
+                }
+                Input {
+                    key: Key::Right, ..
+                } => {
+                    editor.input_area.cancel_selection();
+                    move_cursor_count(&mut editor.input_area, CursorMove::Forward, count);
+                }
+                Input {
+                    key: Key::Char(' '),
+                    ..
+                } => {
+                    editor.input_area.cancel_selection();
+                    move_cursor_count(&mut editor.input_area, CursorMove::Forward, count);
+                    editor.status_bar.last_command = "| >";
+                }
+                Input {
+                    key: Key::Backspace,
+                    ..
+                } => {
+                    editor.input_area.cancel_selection();
+                    move_cursor_count(&mut editor.input_area, CursorMove::Back, count);
+                    editor.status_bar.last_command = "| <";
+                }
+                // Delete char
+                Input {
+                    key: Key::Char('c'),
+                    ctrl: true,
+                    alt: true,
+                    ..
+                } => {
+                    repeat_delete(
+                        &mut editor.input_area,
+                        count,
+                        false,
+                        TextArea::delete_next_char,
+                    );
+                    editor.status_bar.last_command = "| DEL-CHAR";
+                    editor.repeat.is_change = true;
+                }
+                // Move around by word
+                Input {
+                    key: Key::Char('w'),
+                    ctrl: true,
+                    alt: false,
+                    ..
+                } => {
+                    move_cursor_count(&mut editor.input_area, CursorMove::WordForward, count);
+                    editor.status_bar.last_command = "| WORD-FOR";
+                }
+                Input {
+                    key: Key::Char('w'),
+                    ctrl: false,
+                    alt: true,
+                    ..
+                } => {
+                    move_cursor_count(&mut editor.input_area, CursorMove::WordBack, count);
+                    editor.status_bar.last_command = "| WORD-BACK";
+                }
+                // Delete word (forward)
+                Input {
+                    key: Key::Char('w'),
+                    ctrl: true,
+                    alt: true,
+                    ..
+                } => {
+                    repeat_delete(
+                        &mut editor.input_area,
+                        count,
+                        true,
+                        TextArea::delete_next_word,
+                    );
+                    editor.status_bar.last_command = "| DEL-WORD";
+                    editor.repeat.is_change = true;
+                }
+                // Move around by line
+                Input {
+                    key: Key::Char('l'),
+                    ctrl: true,
+                    alt: false,
+                    ..
+                } => {
+                    move_cursor_count(&mut editor.input_area, CursorMove::Down, count);
+                    editor.status_bar.last_command = "| LINE-FOR";
+                }
+                Input {
+                    key: Key::Char('l'),
+                    ctrl: false,
+                    alt: true,
+                    ..
+                } => {
+                    move_cursor_count(&mut editor.input_area, CursorMove::Up, count);
+                    editor.status_bar.last_command = "| LINE-BACK";
+                }
+                // Delete line
+                Input {
+                    key: Key::Char('l'),
+                    ctrl: true,
+                    alt: true,
+                    ..
+                } => {
+                    delete_lines(&mut editor.input_area, count);
+                    editor.status_bar.last_command = "| DEL-LINE";
+                    editor.repeat.is_change = true;
+                }
+                // Make a newline
+                Input {
+                    key: Key::Char('n'),
+                    ctrl: true,
+                    alt: false,
+                    ..
+                } => {
+                    editor.input_area.move_cursor(CursorMove::Up);
+                    editor.input_area.move_cursor(CursorMove::End);
+                    insert_newlines(&mut editor.input_area, count, &editor.language);
+                    editor.status_bar.last_command = "| NEW-LINE-UP";
+                    editor.repeat.is_change = true;
+                }
+                Input {
+                    key: Key::Char('n'),
+                    ctrl: false,
+                    alt: true,
+                    ..
+                } => {
+                    editor.input_area.move_cursor(CursorMove::End);
+                    insert_newlines(&mut editor.input_area, count, &editor.language);
+                    editor.status_bar.last_command = "| NEW-LINE-DOWN";
+                    editor.repeat.is_change = true;
+                }
+                // Jump to start/end of line
+                Input {
+                    key: Key::Char('e'),
+                    ctrl: true,
+                    alt: false,
+                    shift: false,
+                } => {
+                    editor.input_area.move_cursor(CursorMove::Head);
+                    editor.status_bar.last_command = "| JUMP-LINE-START";
+                }
+                Input {
+                    key: Key::Char('e'),
+                    ctrl: false,
+                    alt: true,
+                    shift: false,
+                } => {
+                    editor.input_area.move_cursor(CursorMove::End);
+                    editor.status_bar.last_command = "| JUMP-LINE-END";
+                }
+                // Jump to start/end of line and enter insert mode
+                Input {
+                    key: Key::Char('E'),
+                    ctrl: true,
+                    alt: false,
+                    shift: true,
+                } => {
+                    editor.input_area.move_cursor(CursorMove::Head);
+                    editor.status_bar.last_command = "| JUMP-LINE-START";
+                    enter_insert_mode(editor);
+                }
+                Input {
+                    key: Key::Char('E'),
+                    ctrl: false,
+                    alt: true,
+                    shift: true,
+                } => {
+                    editor.input_area.move_cursor(CursorMove::End);
+                    editor.status_bar.last_command = "| JUMP-LINE-END";
+                    enter_insert_mode(editor);
+                }
+                // Jump to start/end of paragraph
+                Input {
+                    key: Key::Char('p'),
+                    ctrl: true,
+                    alt: false,
+                    ..
+                } => {
+                    record_jump(&mut editor.marks, editor.input_area.cursor());
+                    move_cursor_count(&mut editor.input_area, CursorMove::ParagraphForward, count);
+                    editor.status_bar.last_command = "| JUMP-PAR-FOR";
+                }
+                Input {
+                    key: Key::Char('p'),
+                    ctrl: false,
+                    alt: true,
+                    ..
+                } => {
+                    record_jump(&mut editor.marks, editor.input_area.cursor());
+                    move_cursor_count(&mut editor.input_area, CursorMove::ParagraphBack, count);
+                    editor.status_bar.last_command = "| JUMP-PAR-BACK";
+                }
+                // Delete paragraph (forward)
+                Input {
+                    key: Key::Char('p'),
+                    ctrl: true,
+                    alt: true,
+                    ..
+                } => {
+                    editor.input_area.start_selection();
+                    move_cursor_count(&mut editor.input_area, CursorMove::ParagraphForward, count);
+                    editor.input_area.cut();
+                    editor.input_area.cancel_selection();
+                    editor.status_bar.last_command = "| DEL-PAR-FOR";
+                    editor.repeat.is_change = true;
+                }
+                // Jump to start/end of file
+                Input {
+                    key: Key::Char('j'),
+                    ctrl: true,
+                    alt: false,
+                    ..
+                } => {
+                    record_jump(&mut editor.marks, editor.input_area.cursor());
+                    editor.input_area.move_cursor(CursorMove::Top);
+                    editor.status_bar.last_command = "| JUMP-FILE-START";
+                }
+                Input {
+                    key: Key::Char('j'),
+                    ctrl: false,
+                    alt: true,
+                    ..
+                } => {
+                    record_jump(&mut editor.marks, editor.input_area.cursor());
+                    editor.input_area.move_cursor(CursorMove::Bottom);
+                    editor.status_bar.last_command = "| JUMP-FILE-END";
+                }
+                // Start/stop recording a macro
+                Input {
+                    key: Key::Char('q'),
+                    ctrl: false,
+                    alt: false,
+                    ..
+                } => match editor.macros.recording {
+                    Some(_) => {
+                        stop_recording(&mut editor.macros);
+                        editor.status_bar.last_command = "| REC-STOP";
                     }
-                    Input {
-                        key: Key::Char('e'),
-                        ctrl: false,
-                        alt: true,
-                        shift: false,
-                    } => {
-                        input_area.move_cursor(CursorMove::End);
-                        status_bar.last_command = "| JUMP-LINE-END";
+                    None => {
+                        editor.pending_prefix = Some('q');
                     }
-                    // Jump to start/end of line and enter insert mode
-                    Input {
-                        key: Key::Char('E'),
-                        ctrl: true,
-                        alt: false,
-                        shift: true,
-                    } => {
-                        input_area.move_cursor(CursorMove::Head);
-                        status_bar.last_command = "| JUMP-LINE-START";
-
-                        is_ovr_mode = false;
-                        editor_mode = "Ins";
-                        input_area.set_cursor_style(
-                            Style::default().bg(ratatui::style::Color::LightCyan),
-                        );
-                        input_area.set_cursor_style(
-                            Style::default()
-                                .fg(ratatui::style::Color::LightCyan)
-                                .add_modifier(Modifier::REVERSED),
-                        );
+                },
+                // Replay a macro
+                Input {
+                    key: Key::Char('@'),
+                    ctrl: false,
+                    alt: false,
+                    ..
+                } => {
+                    editor.pending_prefix = Some('@');
+                }
+                // Operators (delete, change, yank), which are followed by a motion or text object
+                Input {
+                    key: Key::Char(operator @ ('d' | 'c' | 'y')),
+                    ctrl: false,
+                    alt: false,
+                    ..
+                } => {
+                    editor.pending_operator = Some(OperatorStruct {
+                        operator,
+                        count,
+                        object: None,
+                    });
+                }
+                // Repeat the last change
+                Input {
+                    key: Key::Char('.'),
+                    ctrl: false,
+                    alt: false,
+                    ..
+                } => {
+                    let keys = get_repeat(&editor.repeat, typed_count);
+                    editor.repeat.is_repeating = true;
+                    editor.macros.replay_depth += 1;
+                    for key in keys {
+                        dispatch_input(editor, key);
                     }
-                    Input {
-                        key: Key::Char('E'),
-                        ctrl: false,
-                        alt: true,
-                        shift: true,
-                    } => {
-                        input_area.move_cursor(CursorMove::End);
-                        status_bar.last_command = "| JUMP-LINE-END";
-
-                        is_ovr_mode = false;
-                        editor_mode = "Ins";
-                        input_area.set_cursor_style(
-                            Style::default().bg(ratatui::style::Color::LightCyan),
-                        );
-                        input_area.set_cursor_style(
-                            Style::default()
-                                .fg(ratatui::style::Color::LightCyan)
-                                .add_modifier(Modifier::REVERSED),
-                        );
+                    editor.macros.replay_depth -= 1;
+                    editor.repeat.is_repeating = false;
+                    // The repeat isn't a change of its own (so . doesn't become the last change), so it is added to
+                    // the undo history here
+                    editor.repeat.is_change = false;
+                    if record_history(&mut editor.history, &editor.input_area) {
+                        editor.is_modified = true;
                     }
-                    // Jump to start/end of paragraph
-                    Input {
-                        key: Key::Char('p'),
-                        ctrl: true,
-                        alt: false,
-                        ..
-                    } => {
-                        input_area.move_cursor(CursorMove::ParagraphForward);
-                        status_bar.last_command = "| JUMP-PAR-FOR";
+                    shift_marks(&mut editor.marks, &take_line_changes(&mut editor.history));
+                    set_mark(&mut editor.marks, '.', editor.input_area.cursor());
+                    editor.status_bar.last_command = "| REPEAT";
+                }
+                // Count for the next command
+                Input {
+                    key: Key::Char(digit @ '0'..='9'),
+                    ctrl: false,
+                    alt: false,
+                    ..
+                } => {
+                    editor.pending_count = add_count_digit(editor.pending_count, digit);
+                }
+                // Set a mark, or jump to one
+                Input {
+                    key: Key::Char(prefix @ ('m' | '\'')),
+                    ctrl: false,
+                    alt: false,
+                    ..
+                } => {
+                    editor.pending_prefix = Some(prefix);
+                }
+                // Go back/forward through the jump list
+                Input {
+                    key: Key::Char('o'),
+                    ctrl: true,
+                    alt: false,
+                    ..
+                } => {
+                    if let Some((row, col)) = jump_forward(&mut editor.marks) {
+                        jump_to(&mut editor.input_area, row, col);
+                        editor.status_bar.last_command = "| JUMP-NEXT";
                     }
-                    Input {
-                        key: Key::Char('p'),
-                        ctrl: false,
-                        alt: true,
-                        ..
-                    } => {
-                        input_area.move_cursor(CursorMove::ParagraphBack);
-                        status_bar.last_command = "| JUMP-PAR-BACK";
+                }
+                Input {
+                    key: Key::Char('o'),
+                    ctrl: false,
+                    alt: true,
+                    ..
+                } => {
+                    let cursor = editor.input_area.cursor();
+                    if let Some((row, col)) = jump_back(&mut editor.marks, cursor) {
+                        jump_to(&mut editor.input_area, row, col);
+                        editor.status_bar.last_command = "| JUMP-PREV";
                     }
-                    // Delete paragraph (forward)
-                    Input {
-                        key: Key::Char('p'),
-                        ctrl: true,
-                        alt: true,
-                        ..
-                    } => {
-                        input_area.start_selection();
-                        input_area.move_cursor(CursorMove::ParagraphForward);
-                        input_area.cut();
-                        input_area.cancel_selection();
-                        status_bar.last_command = "| DEL-PAR-FOR";
+                }
+                // Jump to the bracket matching the one under the cursor
+                Input {
+                    key: Key::Char('%'),
+                    ctrl: false,
+                    alt: false,
+                    ..
+                } => {
+                    let cursor = editor.input_area.cursor();
+                    let lines = editor.input_area.lines();
+                    let position = find_matching_bracket(
+                        &mut editor.brackets,
+                        lines,
+                        cursor,
+                        &editor.language,
+                    );
+                    if let Some((row, col)) = position {
+                        record_jump(&mut editor.marks, cursor);
+                        jump_to(&mut editor.input_area, row, col);
+                        editor.status_bar.last_command = "| JUMP-BRACKET";
                     }
-                    // Jump to start/end of file
-                    Input {
-                        key: Key::Char('j'),
-                        ctrl: true,
-                        alt: false,
-                        ..
-                    } => {
-                        input_area.move_cursor(CursorMove::Top);
-                        status_bar.last_command = "| JUMP-FILE-START";
+                }
+                // Open the command prompt
+                Input {
+                    key: Key::Char(':'),
+                    ctrl: false,
+                    alt: false,
+                    ..
+                } => {
+                    editor.prompt = Some(new_prompt(PromptKind::Command));
+                }
+                // Open the go to prompt
+                Input {
+                    key: Key::Char('g'),
+                    ctrl: true,
+                    alt: false,
+                    ..
+                } => {
+                    editor.prompt = Some(new_prompt(PromptKind::GoTo));
+                }
+                // Go to older/newer states in time, including ones on other branches of the undo tree
+                Input {
+                    key: Key::Char(key @ ('u' | 'r')),
+                    ctrl: false,
+                    alt: true,
+                    ..
+                } => {
+                    let target = get_state_in_time(&editor.history, count, key == 'r');
+                    if go_to_state(&mut editor.history, &mut editor.input_area, target) {
+                        editor.is_modified = true;
                     }
-                    Input {
-                        key: Key::Char('j'),
-                        ctrl: false,
-                        alt: true,
-                        ..
-                    } => {
-                        input_area.move_cursor(CursorMove::Bottom);
-                        status_bar.last_command = "| JUMP-FILE-END";
+                    editor.status_bar.last_command = match key {
+                        'r' => "| UNDO-NEWER",
+                        _ => "| UNDO-OLDER",
+                    };
+                }
+                // Open the undo tree popup
+                Input {
+                    key: Key::Char('u'),
+                    ctrl: true,
+                    alt: false,
+                    ..
+                } => {
+                    editor.history_browser = Some(open_history_browser(&editor.history));
+                }
+                // Open the prompt to go to the file as of some minutes ago
+                Input {
+                    key: Key::Char('u'),
+                    ctrl: true,
+                    alt: true,
+                    ..
+                } => {
+                    editor.prompt = Some(new_prompt(PromptKind::MinutesAgo));
+                }
+                // Undo
+                Input {
+                    key: Key::Char('u'),
+                    ..
+                } => {
+                    // Stop once there is nothing left to undo
+                    for _ in 0..count {
+                        if !undo(&mut editor.history, &mut editor.input_area) {
+                            break;
+                        }
+                        editor.is_modified = true;
                     }
-                    // Undo
-                    Input {
-                        key: Key::Char('u'),
-                        ..
-                    } => {
-                        input_area.undo();
-                        status_bar.last_command = "| UNDO";
+                    editor.status_bar.last_command = "| UNDO";
+                }
+                // Redo
+                Input {
+                    key: Key::Char('r'),
+                    ..
+                } => {
+                    // Stop once there is nothing left to redo
+                    for _ in 0..count {
+                        if !redo(&mut editor.history, &mut editor.input_area) {
+                            break;
+                        }
+                        editor.is_modified = true;
                     }
-                    // Redo
-                    Input {
-                        key: Key::Char('r'),
-                        ..
-                    } => {
-                        input_area.redo();
-                        status_bar.last_command = "| REDO";
+                    editor.status_bar.last_command = "| REDO";
+                }
+                // Indent/dedent count lines (or the selected lines) by one level
+                Input {
+                    key: Key::Char('>'),
+                    ctrl: false,
+                    alt: false,
+                    ..
+                } => {
+                    let (start_row, end_row) = get_line_rows(&editor.input_area, count);
+                    indent_lines(&mut editor.input_area, start_row, end_row);
+                    editor.status_bar.last_command = "| INDENT";
+                    editor.repeat.is_change = true;
+                }
+                Input {
+                    key: Key::Char('<'),
+                    ctrl: false,
+                    alt: false,
+                    ..
+                } => {
+                    let (start_row, end_row) = get_line_rows(&editor.input_area, count);
+                    dedent_lines(&mut editor.input_area, start_row, end_row);
+                    editor.status_bar.last_command = "| DEDENT";
+                    editor.repeat.is_change = true;
+                }
+                // Reflow the paragraph the cursor is in (or the selected lines) to the text width
+                Input {
+                    key: Key::Char('q'),
+                    ctrl: false,
+                    alt: true,
+                    ..
+                } => {
+                    if reflow_text(&mut editor.input_area, editor.text_width, &editor.language) {
+                        editor.status_bar.last_command = "| REFLOW";
+                        editor.repeat.is_change = true;
                     }
-                    // Paste
-                    Input {
-                        key: Key::Char('p'),
-                        ..
-                    } => {
-                        input_area.paste();
-                        status_bar.last_command = "| PASTE";
+                }
+                // Comment/uncomment count lines (or the selected lines), one line at a time or as one block
+                Input {
+                    key: Key::Char('#'),
+                    ctrl: false,
+                    ..
+                } => {
+                    let (start_row, end_row) = get_line_rows(&editor.input_area, count);
+                    let language = &editor.language;
+                    let is_toggled = match input.alt {
+                        true => toggle_block_comment(
+                            &mut editor.input_area,
+                            start_row,
+                            end_row,
+                            language,
+                        ),
+                        false => toggle_line_comments(
+                            &mut editor.input_area,
+                            start_row,
+                            end_row,
+                            language,
+                        ),
+                    };
+                    if is_toggled {
+                        editor.status_bar.last_command = "| COMMENT";
+                        editor.repeat.is_change = true;
                     }
-                    _input => {
-                        // Change is_modified to true, in case a change was made to the input_area
-                        is_modified = true;
+                }
+                // Re-indent the file to the indentation style it mostly uses
+                Input {
+                    key: Key::Char('='),
+                    ctrl: false,
+                    alt: false,
+                    ..
+                } => {
+                    if reindent_lines(&mut editor.input_area) {
+                        editor.status_bar.last_command = "| REINDENT";
+                        editor.repeat.is_change = true;
                     }
                 }
+                // Format the file with the formatter from the config
+                Input {
+                    key: Key::Char('f'),
+                    ctrl: true,
+                    alt: false,
+                    ..
+                } => {
+                    editor.status_bar.last_command = match editor.formatter.clone() {
+                        // Macros are replayed all at once, so the rest of the macro has to wait for the formatter
+                        Some(formatter) if editor.macros.replay_depth > 0 => {
+                            match run_formatter(&mut editor.input_area, &formatter) {
+                                true => "| FORMAT",
+                                false => {
+                                    add_message(
+                                        &mut editor.messages,
+                                        MessageLevel::Error,
+                                        format!("Formatter failed: {formatter}"),
+                                    );
+                                    "| FORMAT-FAILED"
+                                }
+                            }
+                        }
+                        // Otherwise the formatter runs in the background, and its output is used once it finishes
+                        Some(formatter) => {
+                            let text = editor.input_area.lines().join("\n");
+                            let content_hash = get_content_hash(editor.input_area.lines());
+                            spawn_job(&editor.jobs, move || JobResultStruct {
+                                kind: JobKind::Format,
+                                output: format_text(&formatter, text),
+                                command: formatter,
+                                content_hash,
+                            });
+                            "| FORMATTING"
+                        }
+                        None => "| NO-FORMATTER",
+                    };
+                }
+                // Paste
+                Input {
+                    key: Key::Char('p'),
+                    ..
+                } => {
+                    paste_count(&mut editor.input_area, count);
+                    editor.status_bar.last_command = "| PASTE";
+                    editor.repeat.is_change = true;
+                }
+                _input => {
+                    // Change is_modified to true, in case a change was made to the input_area
+                    editor.is_modified = true;
+                }
+            }
+        }
+    }
+    false
+}
+
+// Save the file with the changes file_settings asks for (e.g. trimming trailing whitespace), and its undo history
+// Autosaves leave the lines as they are. In insert mode the undo history isn't saved, as what has been typed so far
+// is part of a step that hasn't finished. Returns false (with an error message) if the file couldn't be saved
+fn save_current_file(editor: &mut EditorStruct, is_autosave: bool) -> bool {
+    if !is_autosave && prepare_save(&mut editor.input_area, &editor.file_settings) {
+        editor.is_modified = true;
+    }
+    let is_history_saved = editor.is_ovr_mode;
+    // The changes made for saving are part of the undo history, so it matches what is on disk
+    if is_history_saved {
+        record_history(&mut editor.history, &editor.input_area);
+    }
+    if let Err(error) = save_file(
+        &editor.is_modified,
+        &editor.file_path,
+        &mut editor.input_area,
+        &editor.file_settings,
+    ) {
+        add_message(
+            &mut editor.messages,
+            MessageLevel::Error,
+            format!("Failed to save {}: {error}", editor.file_path),
+        );
+        return false;
+    }
+    if is_history_saved {
+        save_history(&mut editor.history, &editor.file_path);
+    }
+    editor.file_modified = get_file_modified(&editor.file_path);
+    editor.file_size = get_file_size(&editor.file_path);
+    editor.is_modified = false;
+    refresh_branch(editor);
+    editor.autosave.saved_hash = get_content_hash(editor.input_area.lines());
+    true
+}
+
+// Save the file if it has changed since it was last saved, for autosave. Files aren't autosaved partway through a
+// command (e.g. while a count is being typed). Returns true if anything shown has changed (the file was saved, or
+// couldn't be)
+fn autosave_file(editor: &mut EditorStruct) -> bool {
+    // The idle time starts again, so a file that can't be saved isn't tried again straight away
+    editor.autosave.last_input = Instant::now();
+    let is_busy = editor.prompt.is_some()
+        || editor.pending_prefix.is_some()
+        || editor.pending_operator.is_some()
+        || editor.pending_count > 0;
+    if is_busy || get_content_hash(editor.input_area.lines()) == editor.autosave.saved_hash {
+        return false;
+    }
+    if save_current_file(editor, true) {
+        add_message(
+            &mut editor.messages,
+            MessageLevel::Info,
+            "Autosaved".to_string(),
+        );
+    }
+    true
+}
+
+// Apply the config file again, along with the settings layered over it (the detected indentation and .editorconfig)
+fn reload_config(editor: &mut EditorStruct) {
+    editor.config_modified = get_config_modified();
+    let (config, config_error) = parse_config();
+    // run_config moves the cursor to where it starts in a newly opened file, so keep where it is
+    let (row, col) = editor.input_area.cursor();
+    // Languages can be added or changed in the config, so the file's language is worked out again
+    editor.language = detect_language(&config, &editor.file_path, &editor.input_area.lines()[0]);
+    run_config(
+        &mut editor.input_area,
+        &config,
+        &editor.file_path,
+        &editor.language,
+    );
+    apply_detected_indent(&mut editor.input_area);
+    apply_editorconfig_indent(&get_editorconfig(&editor.file_path), &mut editor.input_area);
+    jump_to(&mut editor.input_area, row, col);
+    editor.formatter = get_config_value(&config, "formatter", &editor.file_path, &editor.language);
+    editor.auto_pairs = get_auto_pairs(&config, &editor.file_path, &editor.language);
+    editor.text_width = get_text_width(
+        &config,
+        &editor.file_path,
+        &editor.language,
+        &editor.file_settings,
+    );
+    apply_wrap_config(
+        &mut editor.wrap,
+        &config,
+        &editor.file_path,
+        &editor.language,
+    );
+    apply_status_config(
+        &mut editor.status_bar,
+        &config,
+        &editor.file_path,
+        &editor.language,
+    );
+    apply_message_config(
+        &mut editor.messages,
+        &config,
+        &editor.file_path,
+        &editor.language,
+    );
+    apply_autosave_config(
+        &mut editor.autosave,
+        &config,
+        &editor.file_path,
+        &editor.language,
+    );
+    if !add_config_messages(&mut editor.messages, &config, config_error) {
+        add_message(
+            &mut editor.messages,
+            MessageLevel::Info,
+            "Config reloaded".to_string(),
+        );
+    }
+}
+
+// Add messages for the config file's parse error or the values in it that can't be used, returns false if there
+// were none
+fn add_config_messages(
+    messages: &mut MessagesStruct,
+    config: &Ini,
+    config_error: Option<String>,
+) -> bool {
+    if let Some(error) = config_error {
+        add_message(messages, MessageLevel::Error, error);
+        return true;
+    }
+    let warnings = get_config_warnings(config);
+    let has_warnings = !warnings.is_empty();
+    for warning in warnings {
+        add_message(messages, MessageLevel::Warning, warning);
+    }
+    has_warnings
+}
+
+// Get whether auto-pairs are on for a file, from the config or its language's default (on if neither says)
+fn get_auto_pairs(config: &Ini, file_path: &String, language: &LanguageStruct) -> bool {
+    !matches!(
+        get_config_value(config, "autopairs", file_path, language).as_deref(),
+        Some("false")
+    )
+}
+
+// Run a command typed into the command prompt
+fn run_command(editor: &mut EditorStruct, command: &str) {
+    match command.trim() {
+        "reload-config" => reload_config(editor),
+        "wrap" => {
+            editor.wrap.is_on = !editor.wrap.is_on;
+            let text = match editor.wrap.is_on {
+                true => "Soft wrap on",
+                false => "Soft wrap off",
+            };
+            add_message(&mut editor.messages, MessageLevel::Info, text.to_string());
+        }
+        "messages" => editor.message_log = Some(open_message_log(&editor.messages)),
+        "" => {
+            // Pass
+        }
+        command => add_message(
+            &mut editor.messages,
+            MessageLevel::Error,
+            format!("Unknown command: {command}"),
+        ),
+    }
+}
+
+// Run the command for a prompt once Enter has been pressed in it
+fn run_prompt(editor: &mut EditorStruct, kind: PromptKind, text: &str) {
+    match kind {
+        PromptKind::GoTo => {
+            let position = parse_goto(text, editor.input_area.lines(), editor.input_area.cursor());
+            if let Some((row, col)) = position {
+                editor.input_area.cancel_selection();
+                record_jump(&mut editor.marks, editor.input_area.cursor());
+                jump_to(&mut editor.input_area, row, col);
+                editor.status_bar.last_command = "| GOTO";
+            }
+        }
+        PromptKind::Command => run_command(editor, text),
+        PromptKind::MinutesAgo => {
+            if let Some(target) = get_state_minutes_ago(&editor.history, text) {
+                if go_to_state(&mut editor.history, &mut editor.input_area, target) {
+                    editor.is_modified = true;
+                }
+                editor.status_bar.last_command = "| UNDO-TIME";
+            }
+        }
+    }
+}
+
+// Handle the keys in the undo tree popup, moving between states previews them in the editor
+fn handle_history_browser(editor: &mut EditorStruct, input: Input) {
+    let browser = editor.history_browser.as_mut().unwrap();
+    match input.key {
+        Key::Char('j') | Key::Down | Key::Char('k') | Key::Up => {
+            let is_down = matches!(input.key, Key::Char('j') | Key::Down);
+            if move_history_browser(
+                browser,
+                &mut editor.history,
+                &mut editor.input_area,
+                is_down,
+            ) {
+                editor.is_modified = true;
+            }
+        }
+        Key::Enter => {
+            editor.history_browser = None;
+            editor.status_bar.last_command = "| UNDO-TREE";
+        }
+        // Go back to the state the file was in when the popup was opened
+        Key::Esc => {
+            let original = browser.original;
+            if go_to_state(&mut editor.history, &mut editor.input_area, original) {
+                editor.is_modified = true;
+            }
+            editor.history_browser = None;
+        }
+        _ => {
+            // Pass
+        }
+    }
+}
+
+// Switch to insert mode
+fn enter_insert_mode(editor: &mut EditorStruct) {
+    // Text typed in insert mode is a change that can be repeated
+    editor.repeat.is_change = true;
+    editor.is_ovr_mode = false;
+    editor.editor_mode = "Ins";
+    editor
+        .input_area
+        .set_cursor_style(Style::default().bg(ratatui::style::Color::LightCyan));
+    editor.input_area.set_cursor_style(
+        Style::default()
+            .fg(ratatui::style::Color::LightCyan)
+            .add_modifier(Modifier::REVERSED),
+    );
+}
+
+// Add a typed digit to the end of a count, keeping it no larger than MAX_COUNT
+fn add_count_digit(count: usize, digit: char) -> usize {
+    count
+        .saturating_mul(10)
+        .saturating_add(digit.to_digit(10).unwrap() as usize)
+        .min(MAX_COUNT)
+}
+
+// Handle the keys after an operator: a count, a motion, or i/a and then a text object
+fn handle_operator(editor: &mut EditorStruct, mut operator: OperatorStruct, input: Input) {
+    let is_plain = !input.ctrl && !input.alt;
+    match (input.key, operator.object) {
+        // Esc cancels the operator
+        (Key::Esc, _) => {
+            editor.pending_count = 0;
+            return;
+        }
+        // A count after the operator multiplies the one before it (0 is a motion unless a count was started)
+        (Key::Char(digit @ '0'..='9'), None)
+            if is_plain && (digit != '0' || editor.pending_count > 0) =>
+        {
+            editor.pending_count = add_count_digit(editor.pending_count, digit);
+            editor.pending_operator = Some(operator);
+            return;
+        }
+        (Key::Char(object @ ('i' | 'a')), None) if is_plain => {
+            operator.object = Some(object);
+            editor.pending_operator = Some(operator);
+            return;
+        }
+        _ => {
+            // Pass
+        }
+    }
+    let count = operator
+        .count
+        .saturating_mul(editor.pending_count.max(1))
+        .min(MAX_COUNT);
+    editor.pending_count = 0;
+    let range = match (operator.object, input.key) {
+        (Some(object), Key::Char(target)) if is_plain => {
+            get_object_range(&editor.input_area, object, target, count)
+        }
+        (Some(_), _) => None,
+        (None, _) => get_motion_range(&mut editor.input_area, operator.operator, &input, count),
+    };
+    if let Some(range) = range {
+        let should_insert = apply_operator(&mut editor.input_area, operator.operator, range);
+        editor.status_bar.last_command = operator_name(operator.operator);
+        if operator.operator != 'y' {
+            editor.is_modified = true;
+            editor.repeat.is_change = true;
+        }
+        if should_insert {
+            enter_insert_mode(editor);
+        }
+    }
+}
+
+// Handle the register key after q (record a macro), @ (replay a macro), m (set a mark) or ' (jump to a mark)
+fn handle_prefix(editor: &mut EditorStruct, prefix: char, input: Input) -> bool {
+    let count = editor.pending_count.max(1);
+    editor.pending_count = 0;
+    let register = match input {
+        Input {
+            key: Key::Char(register),
+            ctrl: false,
+            alt: false,
+            ..
+        } => register,
+        _ => return false,
+    };
+    match prefix {
+        'm' if is_mark_name(register) => {
+            set_mark(&mut editor.marks, register, editor.input_area.cursor());
+            save_marks(&editor.marks, &editor.file_path);
+            editor.status_bar.last_command = "| MARK-SET";
+        }
+        '\'' => {
+            if let Some((row, col)) = get_mark(&editor.marks, register) {
+                editor.input_area.cancel_selection();
+                record_jump(&mut editor.marks, editor.input_area.cursor());
+                jump_to(&mut editor.input_area, row, col);
+                editor.status_bar.last_command = "| JUMP-MARK";
             }
         }
-        // Update the status bar
-        status_bar.cursor_line = &input_area.cursor().0 + 1;
-        status_bar.cursor_row = &input_area.cursor().1 + 1;
-        status_bar.status_content = format!("{cursor_line}{cursor_seperator}{cursor_row}{seperator}{editor_mode}{seperator}{file_type}{seperator}{file_size}{last_command}",
-            cursor_line = &status_bar.cursor_line, cursor_row = &status_bar.cursor_row, last_command = &status_bar.last_command, cursor_seperator = &status_bar.cursor_seperator, seperator = &status_bar.seperator);
-        status_bar.status_text = Text::from(status_bar.status_content);
-        status_bar.status_paragraph =
-            widgets::Paragraph::new(status_bar.status_text).alignment(layout::Alignment::Left);
+        'q' if is_register(register) => {
+            start_recording(&mut editor.macros, register);
+            editor.status_bar.last_command = "| REC-START";
+        }
+        '@' => {
+            // @@ replays the last replayed macro
+            let register = match register {
+                '@' => editor.macros.last_replayed,
+                _ => Some(register),
+            };
+            if let Some(register) = register {
+                editor.status_bar.last_command = "| REPLAY";
+                return replay_macro(editor, register, count);
+            }
+        }
+        _ => {
+            // Pass
+        }
+    }
+    false
+}
+
+// Replay the inputs stored in a register count times, returns true if the program should exit
+fn replay_macro(editor: &mut EditorStruct, register: char, count: usize) -> bool {
+    let inputs = match get_replay(&editor.macros, register) {
+        Some(inputs) => inputs,
+        None => return false,
+    };
+    editor.macros.last_replayed = Some(register);
+    editor.macros.replay_depth += 1;
+    for _ in 0..count {
+        for input in &inputs {
+            if handle_input(editor, input.clone()) {
+                editor.macros.replay_depth -= 1;
+                return true;
+            }
+        }
+    }
+    editor.macros.replay_depth -= 1;
+    false
+}
+
+// Look up the git branch shown in the status bar again
+fn refresh_branch(editor: &mut EditorStruct) {
+    editor.status_bar.branch = get_git_branch(&editor.file_path).unwrap_or_default();
+}
+
+// Count the words and problems shown in the status bar again, if they are shown and the file has changed since they
+// were counted. Problems are lines longer than the .editorconfig max_line_length
+fn update_status_counts(editor: &mut EditorStruct) {
+    let is_shown = editor
+        .status_bar
+        .segments
+        .iter()
+        .flatten()
+        .any(|name| name == "words" || name == "diagnostics");
+    if !is_shown {
+        return;
+    }
+    let lines = editor.input_area.lines();
+    let max_line_length = editor.file_settings.max_line_length;
+    let counted = Some((get_content_hash(lines), max_line_length));
+    if editor.status_bar.counted == counted {
+        return;
     }
+    editor.status_bar.counted = counted;
+    editor.status_bar.words = lines
+        .iter()
+        .map(|line| line.split_whitespace().count())
+        .sum();
+    editor.status_bar.problems = match max_line_length {
+        Some(max_line_length) => lines
+            .iter()
+            .filter(|line| line.chars().count() > max_line_length)
+            .count(),
+        None => 0,
+    };
+}
+
+// Update the status bar, returns true if what it shows has changed
+fn update_status_bar(editor: &mut EditorStruct) -> bool {
+    let content = get_status_content(&editor.status_bar, |name| get_segment_text(editor, name));
+    let is_changed = content != editor.status_bar.content;
+    editor.status_bar.content = content;
+    is_changed
+}
+
+// Get the text of a status bar segment by its name, None if there is no such segment
+fn get_segment_text(editor: &EditorStruct, name: &str) -> Option<String> {
+    let (cursor_row, cursor_col) = editor.input_area.cursor();
+    let lines = editor.input_area.lines();
+    let text = match name {
+        "position" => format!("{}:{}", cursor_row + 1, cursor_col + 1),
+        "mode" => editor.editor_mode.to_string(),
+        "filetype" => editor.language.name.clone(),
+        "filename" => Path::new(&editor.file_path)
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        "modified" => match editor.is_modified {
+            true => "[+]".to_string(),
+            false => "".to_string(),
+        },
+        "encoding" => editor
+            .file_settings
+            .charset
+            .clone()
+            .unwrap_or("utf-8".to_string()),
+        "lineending" => match editor.file_settings.end_of_line {
+            Some("\r\n") => "CRLF".to_string(),
+            Some("\r") => "CR".to_string(),
+            _ => "LF".to_string(),
+        },
+        "indent" => get_indent_name(&editor.input_area),
+        "size" => editor.file_size.clone(),
+        // The selection's length in characters, counting the newlines between its lines
+        "selection" => match editor.input_area.selection_range() {
+            Some(((start_row, start_col), (end_row, end_col))) => {
+                let mut length = end_row - start_row;
+                for (row, line) in lines.iter().enumerate().take(end_row + 1).skip(start_row) {
+                    let line_length = line.chars().count();
+                    let start = if row == start_row { start_col } else { 0 };
+                    let end = if row == end_row { end_col } else { line_length };
+                    length += end.saturating_sub(start);
+                }
+                format!("{length} selected")
+            }
+            None => "".to_string(),
+        },
+        "words" => format!("{} words", editor.status_bar.words),
+        "branch" => editor.status_bar.branch.clone(),
+        "clock" => chrono::Local::now().format("%H:%M").to_string(),
+        "diagnostics" => match editor.status_bar.problems {
+            0 => "".to_string(),
+            1 => "1 problem".to_string(),
+            problems => format!("{problems} problems"),
+        },
+        "command" => editor
+            .status_bar
+            .last_command
+            .trim_start_matches("| ")
+            .to_string(),
+        // The count, operator and text object that are still being typed
+        "pending" => {
+            let mut pending = String::new();
+            if let Some(operator) = &editor.pending_operator {
+                if operator.count > 1 {
+                    pending.push_str(&operator.count.to_string());
+                }
+                pending.push(operator.operator);
+                if let Some(object) = operator.object {
+                    pending.push(object);
+                }
+            }
+            if editor.pending_count > 0 {
+                pending.push_str(&editor.pending_count.to_string());
+            }
+            pending
+        }
+        "recording" => match editor.macros.recording {
+            Some(register) => format!("REC @{register}"),
+            None => "".to_string(),
+        },
+        // Warn when the cursor's line is longer than the .editorconfig max_line_length
+        "linelength" => match editor.file_settings.max_line_length {
+            Some(max_line_length) if lines[cursor_row].chars().count() > max_line_length => {
+                format!("LINE > {max_line_length}")
+            }
+            _ => "".to_string(),
+        },
+        _ => return None,
+    };
+    Some(text)
 }
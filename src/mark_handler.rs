@@ -0,0 +1,134 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use configparser::ini::Ini;
+
+use crate::{config_handler::get_state_dir, file_handler::get_state_name};
+
+// The most positions kept in the jump list
+const MAX_JUMPS: usize = 100;
+
+// Setup the struct which will be used to hold the marks and jump list for the file
+pub struct MarkStruct {
+    // Named marks (a-z, A-Z), and the automatic marks for the last edit (.) and the last jump (')
+    pub marks: HashMap<char, (usize, usize)>,
+    pub jumps: Vec<(usize, usize)>,
+    pub jump_index: usize,
+}
+
+// Only letters can be used for marks that are set by hand
+pub fn is_mark_name(name: char) -> bool {
+    name.is_ascii_alphabetic()
+}
+
+// Get the path to the file a file's marks are saved in
+fn get_mark_path(file_path: &String) -> PathBuf {
+    get_state_dir()
+        .join("marks")
+        .join(get_state_name(file_path))
+}
+
+// Load the marks saved for a file in an earlier session (if there are any)
+pub fn load_marks(file_path: &String) -> MarkStruct {
+    let mut marks = HashMap::new();
+    let mut mark_file = Ini::new_cs();
+    // The path is checked as well, in case another file's path has the same hash
+    let is_loaded = mark_file.load(get_mark_path(file_path)).is_ok()
+        && mark_file.get("file", "path").as_ref() == Some(file_path);
+    if is_loaded {
+        if let Some(section) = mark_file.get_map_ref().get("marks") {
+            for (name, position) in section {
+                let mut name_chars = name.chars();
+                let name = match (name_chars.next(), name_chars.next()) {
+                    (Some(name), None) => Some(name),
+                    _ => None,
+                };
+                let position = position.as_ref().and_then(|position| {
+                    let (row, col) = position.split_once(':')?;
+                    Some((row.parse().ok()?, col.parse().ok()?))
+                });
+                if let (Some(name), Some(position)) = (name, position) {
+                    marks.insert(name, position);
+                }
+            }
+        }
+    }
+    MarkStruct {
+        marks,
+        jumps: Vec::new(),
+        jump_index: 0,
+    }
+}
+
+// Save the named marks and the last edit mark for a file, so they can be used in the next session
+pub fn save_marks(marks: &MarkStruct, file_path: &String) {
+    let mut mark_file = Ini::new_cs();
+    mark_file.set("file", "path", Some(file_path.clone()));
+    for (name, (row, col)) in &marks.marks {
+        if is_mark_name(*name) || *name == '.' {
+            mark_file.set("marks", &name.to_string(), Some(format!("{row}:{col}")));
+        }
+    }
+    let mark_path = get_mark_path(file_path);
+    if let Some(mark_dir) = mark_path.parent() {
+        let _ = fs::create_dir_all(mark_dir);
+    }
+    let _ = mark_file.write(mark_path);
+}
+
+// Move the marks and jump list along with the lines they are on, for each (start, old line count, new line count)
+// change. Positions on lines that were taken out move to the line after them
+pub fn shift_marks(marks: &mut MarkStruct, line_changes: &[(usize, usize, usize)]) {
+    for &(start, old_count, new_count) in line_changes {
+        let shift = |(row, col): (usize, usize)| match row {
+            row if row >= start + old_count => ((row + new_count) - old_count, col),
+            row if row >= start + new_count => (start + new_count, col),
+            row => (row, col),
+        };
+        for position in marks.marks.values_mut().chain(marks.jumps.iter_mut()) {
+            *position = shift(*position);
+        }
+    }
+}
+
+pub fn set_mark(marks: &mut MarkStruct, name: char, position: (usize, usize)) {
+    marks.marks.insert(name, position);
+}
+
+pub fn get_mark(marks: &MarkStruct, name: char) -> Option<(usize, usize)> {
+    marks.marks.get(&name).copied()
+}
+
+// Add the position the cursor is jumping away from to the jump list
+pub fn record_jump(marks: &mut MarkStruct, from: (usize, usize)) {
+    marks.jumps.truncate(marks.jump_index);
+    if marks.jumps.last() != Some(&from) {
+        marks.jumps.push(from);
+    }
+    if marks.jumps.len() > MAX_JUMPS {
+        marks.jumps.remove(0);
+    }
+    marks.jump_index = marks.jumps.len();
+    set_mark(marks, '\'', from);
+}
+
+// Get the position to go back to in the jump list
+pub fn jump_back(marks: &mut MarkStruct, current: (usize, usize)) -> Option<(usize, usize)> {
+    if marks.jump_index == 0 {
+        return None;
+    }
+    // Keep the current position, so jumping forward can come back to it
+    if marks.jump_index == marks.jumps.len() {
+        marks.jumps.push(current);
+    }
+    marks.jump_index -= 1;
+    Some(marks.jumps[marks.jump_index])
+}
+
+// Get the position to go forward to in the jump list
+pub fn jump_forward(marks: &mut MarkStruct) -> Option<(usize, usize)> {
+    if marks.jump_index + 1 >= marks.jumps.len() {
+        return None;
+    }
+    marks.jump_index += 1;
+    Some(marks.jumps[marks.jump_index])
+}
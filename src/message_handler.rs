@@ -0,0 +1,253 @@
+use std::time::{Duration, Instant};
+
+use configparser::ini::Ini;
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+use tui_textarea::{Input, Key};
+
+use crate::{
+    config_handler::get_config_value, language_handler::LanguageStruct,
+    status_handler::parse_style, wrap_handler::get_wrap_starts,
+};
+
+// How many messages are kept in the log, older ones are taken out
+const MESSAGE_LOG_LENGTH: usize = 200;
+// How long a message is shown for when the config doesn't say
+const DEFAULT_MESSAGE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// How important a message is, which sets how it is styled
+#[derive(Clone, Copy, PartialEq)]
+pub enum MessageLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+const MESSAGE_LEVELS: [MessageLevel; 3] = [
+    MessageLevel::Info,
+    MessageLevel::Warning,
+    MessageLevel::Error,
+];
+
+// Setup the struct which holds a message from the editor
+pub struct MessageStruct {
+    pub level: MessageLevel,
+    pub text: String,
+    // The time the message was added, as HH:MM:SS
+    pub time: String,
+}
+
+// Setup the struct which holds the messages the editor has shown, and the one on the message line
+pub struct MessagesStruct {
+    pub log: Vec<MessageStruct>,
+    // Whether the last message in the log is still on the message line, and when it was added
+    is_shown: bool,
+    shown_at: Instant,
+    pub timeout: Duration,
+    // The styles of info, warning and error messages, from the [theme] section of the config
+    styles: [Style; 3],
+}
+
+// Setup the struct which holds the message log popup's state
+pub struct MessageLogStruct {
+    pub selected: usize,
+}
+
+pub fn new_messages() -> MessagesStruct {
+    MessagesStruct {
+        log: Vec::new(),
+        is_shown: false,
+        shown_at: Instant::now(),
+        timeout: DEFAULT_MESSAGE_TIMEOUT,
+        styles: MESSAGE_LEVELS.map(get_default_style),
+    }
+}
+
+// Get the name of a message level, used in the message log and the [theme] section of the config
+fn get_level_name(level: MessageLevel) -> &'static str {
+    match level {
+        MessageLevel::Info => "info",
+        MessageLevel::Warning => "warning",
+        MessageLevel::Error => "error",
+    }
+}
+
+fn get_default_style(level: MessageLevel) -> Style {
+    match level {
+        MessageLevel::Info => Style::default(),
+        MessageLevel::Warning => Style::default().fg(Color::Yellow),
+        MessageLevel::Error => Style::default()
+            .fg(Color::LightRed)
+            .add_modifier(Modifier::BOLD),
+    }
+}
+
+fn get_level_style(messages: &MessagesStruct, level: MessageLevel) -> Style {
+    messages.styles[level as usize]
+}
+
+// Get the message timeout and the styles of messages from the config
+pub fn apply_message_config(
+    messages: &mut MessagesStruct,
+    config: &Ini,
+    file_path: &String,
+    language: &LanguageStruct,
+) {
+    messages.timeout = get_config_value(config, "messagetimeout", file_path, language)
+        .and_then(|timeout| timeout.parse().ok())
+        .filter(|&timeout| timeout > 0)
+        .map_or(DEFAULT_MESSAGE_TIMEOUT, Duration::from_secs);
+    messages.styles = MESSAGE_LEVELS.map(|level| {
+        match config.get("theme", &format!("message.{}", get_level_name(level))) {
+            Some(value) => parse_style(&value),
+            None => get_default_style(level),
+        }
+    });
+}
+
+// Add a message to the log, and show it on the message line
+pub fn add_message(messages: &mut MessagesStruct, level: MessageLevel, text: String) {
+    messages.log.push(MessageStruct {
+        level,
+        text,
+        time: chrono::Local::now().format("%H:%M:%S").to_string(),
+    });
+    if messages.log.len() > MESSAGE_LOG_LENGTH {
+        messages.log.remove(0);
+    }
+    messages.is_shown = true;
+    messages.shown_at = Instant::now();
+}
+
+// Get the message on the message line, if there is one
+pub fn get_shown_message(messages: &MessagesStruct) -> Option<&MessageStruct> {
+    messages.log.last().filter(|_| messages.is_shown)
+}
+
+// Take the message off the message line once it has been shown for the timeout. Returns true if it was taken off
+pub fn expire_message(messages: &mut MessagesStruct) -> bool {
+    let is_expired = messages.is_shown && messages.shown_at.elapsed() >= messages.timeout;
+    if is_expired {
+        messages.is_shown = false;
+    }
+    is_expired
+}
+
+// Draw the message line, if there is a message on it
+pub fn render_message(frame: &mut Frame, area: Rect, messages: &MessagesStruct) {
+    if let Some(message) = get_shown_message(messages) {
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Line::from(Span::styled(
+                message.text.clone(),
+                get_level_style(messages, message.level),
+            )),
+            area,
+        );
+    }
+}
+
+// Open the message log popup, with the newest message selected
+pub fn open_message_log(messages: &MessagesStruct) -> MessageLogStruct {
+    MessageLogStruct {
+        selected: messages.log.len().saturating_sub(1),
+    }
+}
+
+// Handle an input while the message log popup is open. j/k (or the arrow keys) move through the messages, g/G
+// go to the oldest/newest, and Esc, q or Enter close it. Returns false once it is closed
+pub fn message_log_input(
+    log: &mut MessageLogStruct,
+    messages: &MessagesStruct,
+    input: Input,
+) -> bool {
+    let last = messages.log.len().saturating_sub(1);
+    match input.key {
+        Key::Char('j') | Key::Down => log.selected = (log.selected + 1).min(last),
+        Key::Char('k') | Key::Up => log.selected = log.selected.saturating_sub(1),
+        Key::Char('g') => log.selected = 0,
+        Key::Char('G') => log.selected = last,
+        Key::Esc | Key::Char('q') | Key::Enter => return false,
+        _ => {
+            // Pass
+        }
+    }
+    true
+}
+
+// Draw the message log popup over the editor
+pub fn render_message_log(
+    frame: &mut Frame,
+    area: Rect,
+    messages: &MessagesStruct,
+    log: &MessageLogStruct,
+) {
+    let [_, popup_area, _] = Layout::vertical([
+        Constraint::Percentage(15),
+        Constraint::Percentage(70),
+        Constraint::Percentage(15),
+    ])
+    .areas(area);
+    let [_, popup_area, _] = Layout::horizontal([
+        Constraint::Percentage(10),
+        Constraint::Percentage(80),
+        Constraint::Percentage(10),
+    ])
+    .areas(popup_area);
+    // Long messages are wrapped, with the lines after the first lined up with the start of the text
+    let prefix_width = "00:00:00 warning ".len();
+    let text_width = (popup_area.width as usize)
+        .saturating_sub(2 + prefix_width)
+        .max(1);
+    let items: Vec<ListItem> = messages
+        .log
+        .iter()
+        .map(|message| {
+            let chars: Vec<char> = message.text.chars().collect();
+            let mut starts = get_wrap_starts(&message.text, text_width, 4);
+            starts.push(chars.len());
+            let lines: Vec<Line> = starts
+                .windows(2)
+                .enumerate()
+                .map(|(index, range)| {
+                    let text: String = chars[range[0]..range[1]].iter().collect();
+                    match index {
+                        0 => Line::from(vec![
+                            Span::raw(format!("{} ", message.time)),
+                            Span::styled(
+                                format!("{:<7} ", get_level_name(message.level)),
+                                get_level_style(messages, message.level),
+                            ),
+                            Span::raw(text),
+                        ]),
+                        _ => Line::from(format!("{}{text}", " ".repeat(prefix_width))),
+                    }
+                })
+                .collect();
+            ListItem::new(lines)
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title("Messages (j/k: scroll, g/G: oldest/newest, Esc: close)")
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let selected = match messages.log.is_empty() {
+        true => None,
+        false => Some(log.selected),
+    };
+    frame.render_widget(Clear, popup_area);
+    frame.render_stateful_widget(
+        list,
+        popup_area,
+        &mut ListState::default().with_selected(selected),
+    );
+}
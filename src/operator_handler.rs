@@ -0,0 +1,463 @@
+use tui_textarea::{CursorMove, Input, Key, TextArea};
+
+use crate::edit_handler::{jump_to, move_cursor_count};
+
+// Setup the struct which holds an operator (d, c or y) that is waiting for a motion or text object
+pub struct OperatorStruct {
+    pub operator: char,
+    pub count: usize,
+    // Set to 'i' (inside) or 'a' (around) once the start of a text object has been typed
+    pub object: Option<char>,
+}
+
+// Setup the struct which holds the range of text an operator works on (the end is exclusive)
+pub struct RangeStruct {
+    pub start: (usize, usize),
+    pub end: (usize, usize),
+    pub linewise: bool,
+}
+
+// Get the name shown in the status bar for an operator
+pub fn operator_name(operator: char) -> &'static str {
+    match operator {
+        'd' => "| DELETE",
+        'c' => "| CHANGE",
+        _ => "| YANK",
+    }
+}
+
+// Get the range covered by a motion typed after an operator (e.g. the w in dw)
+pub fn get_motion_range(
+    input_area: &mut TextArea,
+    operator: char,
+    input: &Input,
+    count: usize,
+) -> Option<RangeStruct> {
+    let cursor = input_area.cursor();
+    // Typing the operator twice (dd, cc, yy) works on whole lines
+    if input.key == Key::Char(operator) && !input.ctrl && !input.alt {
+        let last_row = (cursor.0 + count - 1).min(input_area.lines().len() - 1);
+        return Some(RangeStruct {
+            start: (cursor.0, 0),
+            end: (last_row, 0),
+            linewise: true,
+        });
+    }
+    // Get the cursor movement for the motion, and whether it includes the character it lands on or whole lines
+    let (cursor_move, is_inclusive, linewise) = match (input.key, input.ctrl, input.alt) {
+        (Key::Char('w'), _, false) => (CursorMove::WordForward, false, false),
+        (Key::Char('b'), false, false) | (Key::Char('w'), false, true) => {
+            (CursorMove::WordBack, false, false)
+        }
+        (Key::Char('e'), false, false) => (CursorMove::WordEnd, true, false),
+        (Key::Char('h') | Key::Backspace | Key::Left, false, false) => {
+            (CursorMove::Back, false, false)
+        }
+        (Key::Char('l') | Key::Char(' ') | Key::Right, false, false) => {
+            (CursorMove::Forward, false, false)
+        }
+        (Key::Char('j') | Key::Down, false, false) | (Key::Char('l'), true, false) => {
+            (CursorMove::Down, false, true)
+        }
+        (Key::Char('k') | Key::Up, false, false) | (Key::Char('l'), false, true) => {
+            (CursorMove::Up, false, true)
+        }
+        (Key::Char('0' | '^'), false, false) | (Key::Char('e'), true, false) => {
+            (CursorMove::Head, false, false)
+        }
+        (Key::Char('$'), false, false) | (Key::Char('e'), false, true) => {
+            (CursorMove::End, false, false)
+        }
+        (Key::Char('}'), false, false) | (Key::Char('p'), true, false) => {
+            (CursorMove::ParagraphForward, false, false)
+        }
+        (Key::Char('{'), false, false) | (Key::Char('p'), false, true) => {
+            (CursorMove::ParagraphBack, false, false)
+        }
+        (Key::Char('g'), false, false) | (Key::Char('j'), true, false) => {
+            (CursorMove::Top, false, true)
+        }
+        (Key::Char('G'), false, false) | (Key::Char('j'), false, true) => {
+            (CursorMove::Bottom, false, true)
+        }
+        _ => return None,
+    };
+    move_cursor_count(input_area, cursor_move, count);
+    let target = input_area.cursor();
+    jump_to(input_area, cursor.0, cursor.1);
+    let (start, mut end) = match target < cursor {
+        true => (target, cursor),
+        false => (cursor, target),
+    };
+    if linewise {
+        return Some(RangeStruct {
+            start: (start.0, 0),
+            end: (end.0, 0),
+            linewise,
+        });
+    }
+    if is_inclusive {
+        end.1 += 1;
+    }
+    Some(RangeStruct {
+        start,
+        end,
+        linewise,
+    })
+}
+
+// Get the range of a text object typed after an operator (e.g. the iw in diw)
+pub fn get_object_range(
+    input_area: &TextArea,
+    object: char,
+    target: char,
+    count: usize,
+) -> Option<RangeStruct> {
+    let is_inner = object == 'i';
+    let lines = input_area.lines();
+    let cursor = input_area.cursor();
+    match target {
+        'w' => get_word_range(&lines[cursor.0], cursor, is_inner),
+        'p' => get_paragraph_range(lines, cursor.0, is_inner),
+        '(' | ')' | 'b' => get_bracket_range(lines, cursor, ('(', ')'), is_inner, count),
+        '[' | ']' => get_bracket_range(lines, cursor, ('[', ']'), is_inner, count),
+        '{' | '}' | 'B' => get_bracket_range(lines, cursor, ('{', '}'), is_inner, count),
+        '<' | '>' => get_bracket_range(lines, cursor, ('<', '>'), is_inner, count),
+        '"' | '\'' | '`' => get_quote_range(&lines[cursor.0], cursor, target, is_inner),
+        't' => get_tag_range(lines, cursor, is_inner, count),
+        _ => None,
+    }
+}
+
+// Run an operator on a range, returns true if the editor should switch to insert mode afterwards
+pub fn apply_operator(input_area: &mut TextArea, operator: char, range: RangeStruct) -> bool {
+    let line_count = input_area.lines().len();
+    let line_length = |input_area: &TextArea, row: usize| input_area.lines()[row].chars().count();
+    let (mut start, mut end) = (range.start, range.end);
+    if range.linewise {
+        match operator {
+            // Changing lines keeps one (empty) line to type into
+            'c' => end = (end.0, line_length(input_area, end.0)),
+            _ if end.0 + 1 < line_count => end = (end.0 + 1, 0),
+            // The last line has no newline after it, so take the one before it instead
+            'd' if start.0 > 0 => {
+                start = (start.0 - 1, line_length(input_area, start.0 - 1));
+                end = (end.0, line_length(input_area, end.0));
+            }
+            _ => end = (end.0, line_length(input_area, end.0)),
+        }
+    }
+    input_area.cancel_selection();
+    jump_to(input_area, start.0, start.1);
+    input_area.start_selection();
+    jump_to(input_area, end.0, end.1);
+    match operator {
+        'y' => {
+            input_area.copy();
+            jump_to(input_area, range.start.0, range.start.1);
+            false
+        }
+        _ => {
+            input_area.cut();
+            if range.linewise && operator == 'd' {
+                let row = range.start.0.min(input_area.lines().len() - 1);
+                jump_to(input_area, row, 0);
+            }
+            operator == 'c'
+        }
+    }
+}
+
+// Sort characters into whitespace, word characters and punctuation
+fn char_class(c: char) -> u8 {
+    match c {
+        c if c.is_whitespace() => 0,
+        c if c.is_alphanumeric() || c == '_' => 1,
+        _ => 2,
+    }
+}
+
+fn get_word_range(line: &str, cursor: (usize, usize), is_inner: bool) -> Option<RangeStruct> {
+    let chars: Vec<char> = line.chars().collect();
+    if chars.is_empty() {
+        return None;
+    }
+    let col = cursor.1.min(chars.len() - 1);
+    let class = char_class(chars[col]);
+    let mut start = col;
+    while start > 0 && char_class(chars[start - 1]) == class {
+        start -= 1;
+    }
+    let mut end = col + 1;
+    while end < chars.len() && char_class(chars[end]) == class {
+        end += 1;
+    }
+    if !is_inner {
+        match class {
+            // Around whitespace also takes the word after it
+            0 => {
+                let next_class = chars.get(end).map(|c| char_class(*c));
+                while end < chars.len() && Some(char_class(chars[end])) == next_class {
+                    end += 1;
+                }
+            }
+            // Around a word takes the whitespace after it, or before it if there is none after
+            _ => {
+                let word_end = end;
+                while end < chars.len() && chars[end].is_whitespace() {
+                    end += 1;
+                }
+                if end == word_end {
+                    while start > 0 && chars[start - 1].is_whitespace() {
+                        start -= 1;
+                    }
+                }
+            }
+        }
+    }
+    Some(RangeStruct {
+        start: (cursor.0, start),
+        end: (cursor.0, end),
+        linewise: false,
+    })
+}
+
+fn get_paragraph_range(lines: &[String], row: usize, is_inner: bool) -> Option<RangeStruct> {
+    let is_blank = |row: usize| lines[row].trim().is_empty();
+    let blank = is_blank(row);
+    let mut start = row;
+    while start > 0 && is_blank(start - 1) == blank {
+        start -= 1;
+    }
+    let mut end = row;
+    while end + 1 < lines.len() && is_blank(end + 1) == blank {
+        end += 1;
+    }
+    if !is_inner && !blank {
+        // Around a paragraph takes the blank lines after it, or before it if there are none after
+        let paragraph_end = end;
+        while end + 1 < lines.len() && is_blank(end + 1) {
+            end += 1;
+        }
+        if end == paragraph_end {
+            while start > 0 && is_blank(start - 1) {
+                start -= 1;
+            }
+        }
+    }
+    Some(RangeStruct {
+        start: (start, 0),
+        end: (end, 0),
+        linewise: true,
+    })
+}
+
+// Flatten the lines into one list of characters, so ranges can span multiple lines
+fn flatten_lines(lines: &[String]) -> Vec<char> {
+    lines.join("\n").chars().collect()
+}
+
+// Convert a (row, col) position into an offset into the flattened lines
+fn to_offset(lines: &[String], position: (usize, usize)) -> usize {
+    lines[..position.0]
+        .iter()
+        .map(|line| line.chars().count() + 1)
+        .sum::<usize>()
+        + position.1
+}
+
+// Convert an offset into the flattened lines back into a (row, col) position
+fn to_position(lines: &[String], mut offset: usize) -> (usize, usize) {
+    for (row, line) in lines.iter().enumerate() {
+        let length = line.chars().count();
+        if offset <= length {
+            return (row, offset);
+        }
+        offset -= length + 1;
+    }
+    let last_row = lines.len() - 1;
+    (last_row, lines[last_row].chars().count())
+}
+
+// Build a range from offsets into the flattened lines
+fn offsets_to_range(lines: &[String], start: usize, end: usize) -> Option<RangeStruct> {
+    Some(RangeStruct {
+        start: to_position(lines, start),
+        end: to_position(lines, end),
+        linewise: false,
+    })
+}
+
+// Find the open bracket enclosing an offset, skipping over bracket pairs that are closed before it
+pub fn find_open_bracket(chars: &[char], offset: usize, pair: (char, char)) -> Option<usize> {
+    let mut depth = 0;
+    for i in (0..offset).rev() {
+        match chars[i] {
+            c if c == pair.1 => depth += 1,
+            c if c == pair.0 && depth == 0 => return Some(i),
+            c if c == pair.0 => depth -= 1,
+            _ => {
+                // Pass
+            }
+        }
+    }
+    None
+}
+
+// Find the close bracket matching the open bracket at an offset
+pub fn find_close_bracket(chars: &[char], open: usize, pair: (char, char)) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in chars.iter().enumerate().skip(open + 1) {
+        match *c {
+            c if c == pair.0 => depth += 1,
+            c if c == pair.1 && depth == 0 => return Some(i),
+            c if c == pair.1 => depth -= 1,
+            _ => {
+                // Pass
+            }
+        }
+    }
+    None
+}
+
+fn get_bracket_range(
+    lines: &[String],
+    cursor: (usize, usize),
+    pair: (char, char),
+    is_inner: bool,
+    count: usize,
+) -> Option<RangeStruct> {
+    let chars = flatten_lines(lines);
+    let offset = to_offset(lines, cursor);
+    // When the cursor is on an open bracket, that bracket starts the first level
+    let mut open = match chars.get(offset) {
+        Some(c) if *c == pair.0 => offset,
+        _ => find_open_bracket(&chars, offset, pair)?,
+    };
+    for _ in 1..count {
+        open = find_open_bracket(&chars, open, pair)?;
+    }
+    let close = find_close_bracket(&chars, open, pair)?;
+    if !is_inner {
+        return offsets_to_range(lines, open, close + 1);
+    }
+    let (mut start, mut end) = (open + 1, close);
+    // Leave the lines holding the brackets alone when the inside spans multiple lines
+    if chars.get(start) == Some(&'\n') {
+        start += 1;
+    }
+    let mut line_start = end;
+    while line_start > start
+        && chars[line_start - 1] != '\n'
+        && chars[line_start - 1].is_whitespace()
+    {
+        line_start -= 1;
+    }
+    if line_start > start && chars[line_start - 1] == '\n' {
+        end = line_start - 1;
+    }
+    offsets_to_range(lines, start, end.max(start))
+}
+
+fn get_quote_range(
+    line: &str,
+    cursor: (usize, usize),
+    quote: char,
+    is_inner: bool,
+) -> Option<RangeStruct> {
+    let chars: Vec<char> = line.chars().collect();
+    // Find every quote in the line that isn't escaped
+    let quotes: Vec<usize> = (0..chars.len())
+        .filter(|i| chars[*i] == quote && (*i == 0 || chars[*i - 1] != '\\'))
+        .collect();
+    // Pair the quotes up, and use the pair around the cursor or else the first pair after it
+    let (open, close) = quotes
+        .chunks_exact(2)
+        .map(|pair| (pair[0], pair[1]))
+        .find(|(_, close)| cursor.1 <= *close)?;
+    match is_inner {
+        true => Some(RangeStruct {
+            start: (cursor.0, open + 1),
+            end: (cursor.0, close),
+            linewise: false,
+        }),
+        false => Some(RangeStruct {
+            start: (cursor.0, open),
+            end: (cursor.0, close + 1),
+            linewise: false,
+        }),
+    }
+}
+
+// Setup the struct which holds the offsets of a matched pair of tags (ends are exclusive)
+struct TagPairStruct {
+    open_start: usize,
+    open_end: usize,
+    close_start: usize,
+    close_end: usize,
+}
+
+// Find every matched pair of tags (e.g. <div>...</div>) in the flattened lines
+fn find_tag_pairs(chars: &[char]) -> Vec<TagPairStruct> {
+    let mut pairs = Vec::new();
+    let mut open_tags: Vec<(String, usize, usize)> = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] != '<' {
+            i += 1;
+            continue;
+        }
+        let tag_end = match chars[i..].iter().position(|c| *c == '>') {
+            Some(position) => i + position + 1,
+            None => break,
+        };
+        let is_closing = chars.get(i + 1) == Some(&'/');
+        let name: String = chars[i + 1 + is_closing as usize..tag_end]
+            .iter()
+            .take_while(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'))
+            .collect();
+        // Skip comments, doctypes and self-closing tags
+        if name.is_empty() || chars[tag_end - 2] == '/' {
+            i = tag_end;
+            continue;
+        }
+        match is_closing {
+            false => open_tags.push((name, i, tag_end)),
+            true => {
+                if let Some(index) = open_tags.iter().rposition(|tag| tag.0 == name) {
+                    let (_, open_start, open_end) = open_tags[index];
+                    open_tags.truncate(index);
+                    pairs.push(TagPairStruct {
+                        open_start,
+                        open_end,
+                        close_start: i,
+                        close_end: tag_end,
+                    });
+                }
+            }
+        }
+        i = tag_end;
+    }
+    pairs
+}
+
+fn get_tag_range(
+    lines: &[String],
+    cursor: (usize, usize),
+    is_inner: bool,
+    count: usize,
+) -> Option<RangeStruct> {
+    let chars = flatten_lines(lines);
+    let offset = to_offset(lines, cursor);
+    let mut pairs: Vec<TagPairStruct> = find_tag_pairs(&chars)
+        .into_iter()
+        .filter(|pair| pair.open_start <= offset && offset < pair.close_end)
+        .collect();
+    // The innermost pair is the first level
+    pairs.sort_by_key(|pair| pair.close_end - pair.open_start);
+    let pair = pairs.get(count - 1)?;
+    match is_inner {
+        true => offsets_to_range(lines, pair.open_end, pair.close_start),
+        false => offsets_to_range(lines, pair.open_start, pair.close_end),
+    }
+}
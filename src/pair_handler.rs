@@ -0,0 +1,64 @@
+use tui_textarea::{CursorMove, TextArea};
+
+use crate::language_handler::LanguageStruct;
+
+// Get the characters that are closed automatically, and what closes them: the language's brackets and quotes
+// (so ' isn't paired in Rust, where it starts lifetimes)
+fn get_pairs(language: &LanguageStruct) -> Vec<(char, char)> {
+    let quotes = language.quotes.chars().map(|quote| (quote, quote));
+    language.brackets.iter().copied().chain(quotes).collect()
+}
+
+// Get the characters before and after the cursor
+fn get_cursor_neighbours(input_area: &TextArea) -> (Option<char>, Option<char>) {
+    let (row, col) = input_area.cursor();
+    let line = &input_area.lines()[row];
+    let before = match col {
+        0 => None,
+        _ => line.chars().nth(col - 1),
+    };
+    (before, line.chars().nth(col))
+}
+
+// Handle a character typed in insert mode: an opener also inserts its closer, and typing a closer that is already
+// after the cursor steps past it. Returns false if the character should just be inserted
+pub fn insert_pair_char(input_area: &mut TextArea, typed: char, language: &LanguageStruct) -> bool {
+    let pairs = get_pairs(language);
+    let (before, after) = get_cursor_neighbours(input_area);
+    let is_closer = pairs.iter().any(|&(_, closer)| closer == typed);
+    if is_closer && after == Some(typed) {
+        input_area.move_cursor(CursorMove::Forward);
+        return true;
+    }
+    let closer = match pairs.iter().find(|&&(opener, _)| opener == typed) {
+        Some(&(_, closer)) => closer,
+        None => return false,
+    };
+    // Only pair up when the next character is a space, a closer or the end of the line, so typing before a word
+    // doesn't need the closer deleting
+    let is_free_after = after.is_none_or(|after| {
+        after.is_whitespace() || pairs.iter().any(|&(_, closer)| closer == after)
+    });
+    // Quotes after a letter are more often an apostrophe (e.g. don't) than a string
+    let is_quote = typed == closer;
+    if !is_free_after || (is_quote && before.is_some_and(char::is_alphanumeric)) {
+        return false;
+    }
+    input_area.insert_char(typed);
+    input_area.insert_char(closer);
+    input_area.move_cursor(CursorMove::Back);
+    true
+}
+
+// Delete both characters of an empty pair the cursor is between (for Backspace). Returns false if it isn't in one
+pub fn delete_pair(input_area: &mut TextArea, language: &LanguageStruct) -> bool {
+    let is_in_pair = match get_cursor_neighbours(input_area) {
+        (Some(before), Some(after)) => get_pairs(language).contains(&(before, after)),
+        _ => false,
+    };
+    if is_in_pair {
+        input_area.delete_char();
+        input_area.delete_next_char();
+    }
+    is_in_pair
+}
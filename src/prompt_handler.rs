@@ -0,0 +1,75 @@
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Modifier, Style},
+    widgets::Paragraph,
+    Frame,
+};
+use tui_textarea::{Input, Key, TextArea};
+
+// The prompts the editor can open, which say what is done with the text typed into them
+#[derive(Clone, Copy, PartialEq)]
+pub enum PromptKind {
+    // A : command
+    Command,
+    // A position to go to
+    GoTo,
+    // How many minutes back in the undo history to go
+    MinutesAgo,
+}
+
+// Setup the struct which will be used for a prompt shown in place of the status bar
+pub struct PromptStruct<'a> {
+    pub kind: PromptKind,
+    // The label shown before the text typed into the prompt
+    pub label: &'static str,
+    pub prompt_area: TextArea<'a>,
+}
+
+pub fn new_prompt<'a>(kind: PromptKind) -> PromptStruct<'a> {
+    let mut prompt_area = TextArea::default();
+    prompt_area.set_cursor_line_style(Style::default());
+    prompt_area.set_cursor_style(Style::default().add_modifier(Modifier::REVERSED));
+    let label = match kind {
+        PromptKind::Command => ":",
+        PromptKind::GoTo => "Go to: ",
+        PromptKind::MinutesAgo => "Minutes ago: ",
+    };
+    PromptStruct {
+        kind,
+        label,
+        prompt_area,
+    }
+}
+
+// Add an input to the prompt, returns the text typed into it once Enter is pressed
+// Esc closes the prompt without returning any text
+pub fn prompt_input(prompt: &mut Option<PromptStruct>, input: Input) -> Option<String> {
+    match input {
+        Input { key: Key::Esc, .. } => {
+            *prompt = None;
+            None
+        }
+        Input {
+            key: Key::Enter, ..
+        } => prompt
+            .take()
+            .map(|prompt| prompt.prompt_area.lines()[0].clone()),
+        input => {
+            if let Some(prompt) = prompt {
+                prompt.prompt_area.input(input);
+            }
+            None
+        }
+    }
+}
+
+// Draw the prompt's label and the text typed into it
+pub fn render_prompt(frame: &mut Frame, area: Rect, prompt: &PromptStruct) {
+    let [label_area, text_area] = Layout::horizontal([
+        Constraint::Length(prompt.label.chars().count() as u16),
+        Constraint::Min(0),
+    ])
+    .areas(area);
+    frame.render_widget(Paragraph::new(prompt.label), label_area);
+    frame.render_widget(&prompt.prompt_area, text_area);
+}
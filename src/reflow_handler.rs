@@ -0,0 +1,371 @@
+use configparser::ini::Ini;
+use tui_textarea::TextArea;
+
+use crate::{
+    config_handler::get_config_value,
+    edit_handler::{jump_to, replace_rows},
+    file_handler::FileSettingsStruct,
+    indent_handler::get_line_indent,
+    language_handler::LanguageStruct,
+    wrap_handler::get_char_width,
+};
+
+// The width text is reflowed to when neither the config nor .editorconfig sets one
+const DEFAULT_TEXT_WIDTH: usize = 80;
+
+// Setup the struct which holds a line split into the markers at its start and its text
+struct LineStruct {
+    // The indentation, comment token and quote markers, which are kept on every line the text is reflowed onto
+    lead: String,
+    // A list bullet or number (and the space after it), which starts a new list item
+    bullet: String,
+    text: String,
+}
+
+// Setup the struct which holds a paragraph or list item while its words are collected
+struct ItemStruct {
+    // The prefix of the item's first line, and of the lines after it
+    first_prefix: String,
+    next_prefix: String,
+    // The lead of the item's lines with the whitespace taken out, from get_lead_kind
+    kind: String,
+    words: Vec<String>,
+}
+
+// Get the width to reflow a file's text to, from max_line_length in .editorconfig or textwidth in the config
+pub fn get_text_width(
+    config: &Ini,
+    file_path: &String,
+    language: &LanguageStruct,
+    file_settings: &FileSettingsStruct,
+) -> usize {
+    let config_width = get_config_value(config, "textwidth", file_path, language)
+        .and_then(|width| width.parse().ok());
+    file_settings
+        .max_line_length
+        .or(config_width)
+        .filter(|&width| width > 0)
+        .unwrap_or(DEFAULT_TEXT_WIDTH)
+}
+
+// Take the characters at the start of some text that match, returning them and the rest of the text
+fn split_start(text: &str, matches: impl Fn(char) -> bool) -> (&str, &str) {
+    let rest = text.trim_start_matches(matches);
+    text.split_at(text.len() - rest.len())
+}
+
+// Split a line into its lead (indentation, comment token, quote markers), list bullet and text
+fn parse_line(line: &str, language: &LanguageStruct) -> LineStruct {
+    let (indent, mut rest) = split_start(line, char::is_whitespace);
+    let mut lead = indent.to_string();
+    let mut take = |length: usize, rest: &mut &str| {
+        lead.push_str(&rest[..length]);
+        *rest = &rest[length..];
+        let (space, after) = split_start(rest, char::is_whitespace);
+        lead.push_str(space);
+        *rest = after;
+    };
+    let line_comment = language.line_comment.as_deref().unwrap_or_default();
+    let is_c_block = language
+        .block_comment
+        .as_ref()
+        .is_some_and(|(start, _)| start == "/*");
+    if !line_comment.is_empty() && rest.starts_with(line_comment) {
+        // Doc comments repeat the token's last character or add a ! after it (e.g. /// and //!)
+        let (extra, _) = split_start(&rest[line_comment.len()..], |c| {
+            c == '!' || line_comment.ends_with(c)
+        });
+        take(line_comment.len() + extra.len(), &mut rest);
+    } else if is_c_block && (rest == "*" || rest.starts_with("* ")) {
+        // The * at the start of each line inside a /* */ comment
+        take(1, &mut rest);
+    }
+    // Markdown (and email) quote markers
+    while rest.starts_with('>') {
+        take(1, &mut rest);
+    }
+    let (number, after_number) = split_start(rest, |c| c.is_ascii_digit());
+    let bullet_length = match rest.chars().next() {
+        Some('-' | '*' | '+') => 1,
+        Some(_)
+            if !number.is_empty()
+                && (after_number.starts_with(". ") || after_number.starts_with(") ")) =>
+        {
+            number.len() + 1
+        }
+        _ => 0,
+    };
+    let (bullet, text) = match bullet_length > 0 && rest[bullet_length..].starts_with(' ') {
+        true => {
+            let (space, text) = split_start(&rest[bullet_length..], |c| c == ' ');
+            (format!("{}{space}", &rest[..bullet_length]), text)
+        }
+        false => (String::new(), rest),
+    };
+    LineStruct {
+        lead,
+        bullet,
+        text: text.to_string(),
+    }
+}
+
+// Get how many columns some text takes up, with tabs going to the next tab stop
+fn get_text_columns(text: &str, tab_length: u8) -> usize {
+    let mut columns = 0;
+    for c in text.chars() {
+        columns += get_char_width(c, columns, tab_length);
+    }
+    columns
+}
+
+// Fill lines with words up to the width, starting the first line with first_prefix and the others with next_prefix
+// A word longer than the width goes on a line of its own
+fn fill_words(
+    words: &[String],
+    first_prefix: &str,
+    next_prefix: &str,
+    width: usize,
+    tab_length: u8,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = first_prefix.to_string();
+    let mut has_words = false;
+    for word in words {
+        if has_words && get_text_columns(&format!("{line} {word}"), tab_length) > width {
+            lines.push(line);
+            line = next_prefix.to_string();
+            has_words = false;
+        }
+        if has_words {
+            line.push(' ');
+        }
+        line.push_str(word);
+        has_words = true;
+    }
+    lines.push(line);
+    lines
+}
+
+// Check whether a line's lead ends in enough indentation to make its text an indented code block (four spaces, or
+// five after a comment token or quote marker, as one space always comes after those)
+fn is_code_indent(lead: &str, tab_length: u8) -> bool {
+    let markers = lead.trim_end();
+    let indent = get_text_columns(&lead[markers.len()..], tab_length);
+    match markers.trim_start().is_empty() {
+        true => indent >= 4,
+        false => indent >= 5,
+    }
+}
+
+// Get which rows of a Markdown file are never reflowed: headings (e.g. # Title), code fences (``` or ~~~) and the
+// lines between them, and indented code blocks (which start after an empty line). These rows split paragraphs, as
+// empty lines do. Other languages have no kept rows, as these mean other things in them (e.g. # starts a comment)
+fn get_kept_rows(lines: &[String], language: &LanguageStruct, tab_length: u8) -> Vec<bool> {
+    if language.id != "markdown" {
+        return vec![false; lines.len()];
+    }
+    let mut kept = Vec::with_capacity(lines.len());
+    // The fence the rows are inside, which only a line starting with the same fence closes
+    let mut fence: Option<&str> = None;
+    let mut is_after_break = true;
+    for line in lines {
+        let parsed = parse_line(line, language);
+        let text = format!("{}{}", parsed.bullet, parsed.text);
+        let line_fence = ["```", "~~~"]
+            .into_iter()
+            .find(|line_fence| text.starts_with(line_fence));
+        let (hashes, after_hashes) = split_start(&text, |c| c == '#');
+        let is_kept = match fence {
+            Some(open_fence) => {
+                if line_fence == Some(open_fence) {
+                    fence = None;
+                }
+                true
+            }
+            None if line_fence.is_some() => {
+                fence = line_fence;
+                true
+            }
+            None => {
+                let is_heading = (1..=6).contains(&hashes.len())
+                    && (after_hashes.is_empty() || after_hashes.starts_with(' '));
+                let is_code = is_after_break
+                    && !text.trim().is_empty()
+                    && is_code_indent(&parsed.lead, tab_length);
+                is_heading || is_code
+            }
+        };
+        is_after_break = is_kept || text.trim().is_empty();
+        kept.push(is_kept);
+    }
+    kept
+}
+
+// Get the lead of a line with the whitespace taken out, which lines must share to be reflowed together
+fn get_lead_kind(line: &LineStruct) -> String {
+    line.lead.split_whitespace().collect()
+}
+
+// Get the rows to reflow when there is no selection: the lines around the cursor up to the empty lines before and
+// after it (the same paragraphs CursorMove::ParagraphForward moves between), that have the same kind of lead as the
+// cursor's line (so a comment isn't reflowed together with the code after it). Kept rows end paragraphs as well
+fn get_paragraph_rows(
+    lines: &[String],
+    kept: &[bool],
+    row: usize,
+    language: &LanguageStruct,
+) -> Option<(usize, usize)> {
+    if lines[row].is_empty() || kept[row] {
+        return None;
+    }
+    let kind = get_lead_kind(&parse_line(&lines[row], language));
+    let is_in_paragraph = |row: usize| {
+        !lines[row].is_empty()
+            && !kept[row]
+            && get_lead_kind(&parse_line(&lines[row], language)) == kind
+    };
+    let mut start = row;
+    while start > 0 && is_in_paragraph(start - 1) {
+        start -= 1;
+    }
+    let mut end = row;
+    while end + 1 < lines.len() && is_in_paragraph(end + 1) {
+        end += 1;
+    }
+    Some((start, end))
+}
+
+// Reflow the selected rows, or the paragraph the cursor is in, so each line is as full as it can be without going
+// past the width. Each line's lead is kept, and list items are reflowed on their own, with the lines after the
+// first lined up with the item's text. Markdown headings and code are left as they are. Returns false if there was
+// nothing to reflow
+pub fn reflow_text(input_area: &mut TextArea, width: usize, language: &LanguageStruct) -> bool {
+    let tab_length = input_area.tab_length();
+    let kept = get_kept_rows(input_area.lines(), language, tab_length);
+    let (start_row, end_row) = match input_area.selection_range() {
+        Some(((start_row, _), (end_row, _))) => (start_row, end_row),
+        None => {
+            match get_paragraph_rows(input_area.lines(), &kept, input_area.cursor().0, language) {
+                Some(rows) => rows,
+                None => return false,
+            }
+        }
+    };
+    let mut new_lines: Vec<String> = Vec::new();
+    let mut items: Vec<ItemStruct> = Vec::new();
+    // Lines with no text (e.g. an empty comment line) split paragraphs, so are kept as they are, as are kept rows
+    // The item they come after is filled before them
+    let fill_items = |items: &mut Vec<ItemStruct>, new_lines: &mut Vec<String>| {
+        for item in items.drain(..) {
+            new_lines.extend(fill_words(
+                &item.words,
+                &item.first_prefix,
+                &item.next_prefix,
+                width,
+                tab_length,
+            ));
+        }
+    };
+    let rows = input_area.lines()[start_row..=end_row]
+        .iter()
+        .zip(&kept[start_row..=end_row]);
+    for (line, &is_kept) in rows {
+        if is_kept {
+            fill_items(&mut items, &mut new_lines);
+            new_lines.push(line.clone());
+            continue;
+        }
+        let parsed = parse_line(line, language);
+        if parsed.text.trim().is_empty() && parsed.bullet.is_empty() {
+            fill_items(&mut items, &mut new_lines);
+            new_lines.push(line.trim_end().to_string());
+            continue;
+        }
+        let kind = get_lead_kind(&parsed);
+        let is_continued =
+            parsed.bullet.is_empty() && items.last().is_some_and(|item| item.kind == kind);
+        if !is_continued {
+            let next_prefix = match parsed.bullet.is_empty() {
+                true => parsed.lead.clone(),
+                false => format!(
+                    "{}{}",
+                    parsed.lead,
+                    " ".repeat(get_text_columns(&parsed.bullet, tab_length))
+                ),
+            };
+            items.push(ItemStruct {
+                first_prefix: format!("{}{}", parsed.lead, parsed.bullet),
+                next_prefix,
+                kind,
+                words: Vec::new(),
+            });
+        }
+        if let Some(item) = items.last_mut() {
+            item.words
+                .extend(parsed.text.split_whitespace().map(String::from));
+        }
+    }
+    fill_items(&mut items, &mut new_lines);
+    let new_text = new_lines.join("\n");
+    if input_area.lines()[start_row..=end_row].join("\n") != new_text {
+        replace_rows(input_area, start_row, end_row, &new_text);
+    }
+    input_area.cancel_selection();
+    jump_to(
+        input_area,
+        start_row,
+        get_line_indent(&input_area.lines()[start_row])
+            .chars()
+            .count(),
+    );
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config_handler::new_config_parser, language_handler::detect_language};
+
+    fn reflow(file_path: &str, lines: &[&str], row: usize) -> Vec<String> {
+        let language = detect_language(&new_config_parser(), &file_path.to_string(), lines[0]);
+        let mut input_area = TextArea::from(lines.iter().map(|line| line.to_string()));
+        jump_to(&mut input_area, row, 0);
+        reflow_text(&mut input_area, 30, &language);
+        input_area.lines().to_vec()
+    }
+
+    #[test]
+    fn indented_paragraph_is_reflowed_outside_markdown() {
+        let lines = [
+            "def f():",
+            "    \"\"\"Summary.",
+            "",
+            "    Some words that",
+            "    go on and on",
+            "",
+            "    \"\"\"",
+        ];
+        assert_eq!(
+            reflow("f.py", &lines, 3)[3..5],
+            ["    Some words that go on and", "    on"]
+        );
+    }
+
+    #[test]
+    fn markdown_code_and_headings_are_kept() {
+        let lines = ["# A heading", "", "    some code", "    more code"];
+        assert_eq!(reflow("a.md", &lines, 0), lines);
+        assert_eq!(reflow("a.md", &lines, 2), lines);
+        let lines = ["```", "one", "two", "```"];
+        assert_eq!(reflow("a.md", &lines, 1), lines);
+    }
+
+    #[test]
+    fn markdown_paragraph_is_reflowed() {
+        let lines = ["# Title", "one", "two", "```", "three"];
+        assert_eq!(
+            reflow("a.md", &lines, 1),
+            ["# Title", "one two", "```", "three"]
+        );
+    }
+}
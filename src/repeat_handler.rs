@@ -0,0 +1,140 @@
+use tui_textarea::{Input, Key};
+
+// Setup the struct which keeps track of the last change, so it can be repeated with the . key
+pub struct RepeatStruct {
+    // The keys of the command being typed (and any text typed in the insert mode it started)
+    pub keys: Vec<Input>,
+    // The keys of the last command that changed the text
+    pub last_change: Vec<Input>,
+    // Set by commands that change the text
+    pub is_change: bool,
+    // Set while the last change is being repeated, so the repeat isn't recorded over itself
+    pub is_repeating: bool,
+}
+
+pub fn new_repeat() -> RepeatStruct {
+    RepeatStruct {
+        keys: Vec::new(),
+        last_change: Vec::new(),
+        is_change: false,
+        is_repeating: false,
+    }
+}
+
+// Add a key to the command being typed
+pub fn record_key(repeat: &mut RepeatStruct, input: &Input) {
+    if !repeat.is_repeating && input.key != Key::Null {
+        repeat.keys.push(input.clone());
+    }
+}
+
+// Called after every key, keeps the keys of a finished command if it changed the text
+pub fn finish_command(repeat: &mut RepeatStruct, is_ovr_mode: bool, is_pending: bool) {
+    if repeat.is_repeating || is_pending {
+        return;
+    }
+    match (is_ovr_mode, repeat.is_change) {
+        // The text typed in insert mode is part of the change that started it, so wait for the switch back
+        (false, true) => {
+            // Pass
+        }
+        (true, true) => {
+            repeat.last_change = std::mem::take(&mut repeat.keys);
+            repeat.is_change = false;
+        }
+        (_, false) => {
+            repeat.keys.clear();
+        }
+    }
+}
+
+fn is_digit(input: &Input) -> bool {
+    matches!(input.key, Key::Char('0'..='9'))
+}
+
+// Get the keys to replay for the last change, with its count swapped out if a new one was typed. The whole count
+// is swapped, including the count after an operator (e.g. 5. after d3w deletes five words, not fifteen)
+// Nothing is replayed before there has been a change, as the count alone would be kept for the next key
+pub fn get_repeat(repeat: &RepeatStruct, count: usize) -> Vec<Input> {
+    if repeat.last_change.is_empty() {
+        return Vec::new();
+    }
+    let mut keys: Vec<Input> = match count {
+        0 => return repeat.last_change.clone(),
+        _ => count
+            .to_string()
+            .chars()
+            .map(|digit| Input {
+                key: Key::Char(digit),
+                ..Input::default()
+            })
+            .collect(),
+    };
+    let mut change: Vec<Input> = repeat
+        .last_change
+        .iter()
+        .skip_while(|input| is_digit(input))
+        .cloned()
+        .collect();
+    let is_operator = matches!(
+        change.first(),
+        Some(Input {
+            key: Key::Char('d' | 'c' | 'y'),
+            ctrl: false,
+            alt: false,
+            ..
+        })
+    );
+    // 0 straight after an operator is a motion (to the start of the line), not a count
+    if is_operator
+        && change
+            .get(1)
+            .is_some_and(|input| input.key != Key::Char('0'))
+    {
+        let motion_count = change[1..]
+            .iter()
+            .take_while(|input| is_digit(input))
+            .count();
+        change.drain(1..1 + motion_count);
+    }
+    keys.extend(change);
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_repeat_keys(last_change: &str, count: usize) -> String {
+        let mut repeat = new_repeat();
+        repeat.last_change = last_change
+            .chars()
+            .map(|c| Input {
+                key: Key::Char(c),
+                ..Input::default()
+            })
+            .collect();
+        get_repeat(&repeat, count)
+            .iter()
+            .map(|input| match input.key {
+                Key::Char(c) => c,
+                _ => '?',
+            })
+            .collect()
+    }
+
+    #[test]
+    fn nothing_is_repeated_before_a_change() {
+        assert_eq!(get_repeat_keys("", 5), "");
+        assert_eq!(get_repeat_keys("", 0), "");
+    }
+
+    #[test]
+    fn count_replaces_whole_count() {
+        assert_eq!(get_repeat_keys("d3w", 0), "d3w");
+        assert_eq!(get_repeat_keys("d3w", 5), "5dw");
+        assert_eq!(get_repeat_keys("2d3w", 12), "12dw");
+        assert_eq!(get_repeat_keys("d0", 4), "4d0");
+        assert_eq!(get_repeat_keys("3x", 2), "2x");
+    }
+}
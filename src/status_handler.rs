@@ -0,0 +1,198 @@
+use std::{fs, path::Path};
+
+use configparser::ini::Ini;
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    Frame,
+};
+
+use crate::{config_handler::get_config_value, language_handler::LanguageStruct};
+
+// The segments shown when the config doesn't say, which is what the status bar has always shown
+const DEFAULT_LEFT_SEGMENTS: &str =
+    "position mode filetype indent size command pending recording linelength";
+
+// Setup the struct which will be used for the status bar
+pub struct StatusBarStruct<'a> {
+    pub last_command: &'a str,
+    pub seperator: String,
+    // The names of the segments shown on the left, in the center and on the right of the status bar
+    pub segments: [Vec<String>; 3],
+    // The style of the whole status bar, and the styles of segments by name, from the [theme] section of the config
+    pub style: Style,
+    pub segment_styles: Vec<(String, Style)>,
+    // The text of each segment that is shown, with its style, filled in when the status bar is updated
+    pub content: [Vec<Span<'static>>; 3],
+    // The git branch, which is only looked up again when the file is saved or changed, or the editor gets focus
+    pub branch: String,
+    // The number of words and problems in the file, and the content hash and max line length they were counted for,
+    // so they are only counted again when the file changes
+    pub words: usize,
+    pub problems: usize,
+    pub counted: Option<(u64, Option<usize>)>,
+}
+
+pub fn new_status_bar<'a>() -> StatusBarStruct<'a> {
+    StatusBarStruct {
+        last_command: "",
+        seperator: " | ".to_string(),
+        segments: [
+            get_segment_names(DEFAULT_LEFT_SEGMENTS),
+            Vec::new(),
+            Vec::new(),
+        ],
+        style: Style::default(),
+        segment_styles: Vec::new(),
+        content: [Vec::new(), Vec::new(), Vec::new()],
+        branch: String::new(),
+        words: 0,
+        problems: 0,
+        counted: None,
+    }
+}
+
+fn get_segment_names(value: &str) -> Vec<String> {
+    value
+        .split_whitespace()
+        .map(|name| name.to_lowercase())
+        .collect()
+}
+
+// Get the status bar's segments, separator and theme from the config
+pub fn apply_status_config(
+    status_bar: &mut StatusBarStruct,
+    config: &Ini,
+    file_path: &String,
+    language: &LanguageStruct,
+) {
+    let keys = ["statusleft", "statuscenter", "statusright"];
+    for (segments, key) in status_bar.segments.iter_mut().zip(keys) {
+        *segments = match get_config_value(config, key, file_path, language) {
+            Some(value) => get_segment_names(&value),
+            None if key == "statusleft" => get_segment_names(DEFAULT_LEFT_SEGMENTS),
+            None => Vec::new(),
+        };
+    }
+    // Quotes let the separator start or end with spaces, which the config would otherwise take off
+    status_bar.seperator = match get_config_value(config, "statusseparator", file_path, language) {
+        Some(seperator) => seperator.trim_matches('"').to_string(),
+        None => " | ".to_string(),
+    };
+    status_bar.style = config
+        .get("theme", "status")
+        .map(|value| parse_style(&value))
+        .unwrap_or_default();
+    status_bar.segment_styles = Vec::new();
+    if let Some(theme) = config.get_map_ref().get("theme") {
+        for (key, value) in theme {
+            if let (Some(name), Some(value)) = (key.strip_prefix("status."), value) {
+                status_bar
+                    .segment_styles
+                    .push((name.to_string(), parse_style(value)));
+            }
+        }
+    }
+}
+
+// Parse a style from the theme, such as "bold black on lightcyan". The first colour is the text colour, a colour
+// after "on" is the background, and any modifiers (bold, italic, etc) are added. Words that aren't any of these
+// are left out
+pub fn parse_style(value: &str) -> Style {
+    let mut style = Style::default();
+    let mut is_background = false;
+    for word in value.split_whitespace() {
+        let word = word.to_lowercase();
+        let modifier = match word.as_str() {
+            "on" => {
+                is_background = true;
+                continue;
+            }
+            "bold" => Modifier::BOLD,
+            "dim" => Modifier::DIM,
+            "italic" => Modifier::ITALIC,
+            "underlined" | "underline" => Modifier::UNDERLINED,
+            "reversed" | "reverse" => Modifier::REVERSED,
+            "crossedout" | "crossed_out" => Modifier::CROSSED_OUT,
+            "slowblink" | "blink" => Modifier::SLOW_BLINK,
+            _ => Modifier::empty(),
+        };
+        if !modifier.is_empty() {
+            style = style.add_modifier(modifier);
+            continue;
+        }
+        if let Ok(color) = word.parse::<Color>() {
+            style = match is_background {
+                true => style.bg(color),
+                false => style.fg(color),
+            };
+            is_background = false;
+        }
+    }
+    style
+}
+
+// Get the text of the status bar's segments with their styles, using get_text to get the text of a segment by its
+// name. Segments with no text (e.g. recording when no macro is being recorded) are left out, along with their
+// separator
+pub fn get_status_content(
+    status_bar: &StatusBarStruct,
+    get_text: impl Fn(&str) -> Option<String>,
+) -> [Vec<Span<'static>>; 3] {
+    status_bar.segments.clone().map(|segments| {
+        let mut content = Vec::new();
+        for name in &segments {
+            let text = match get_text(name) {
+                Some(text) if !text.trim().is_empty() => text.trim().to_string(),
+                _ => continue,
+            };
+            if !content.is_empty() {
+                content.push(Span::raw(status_bar.seperator.clone()));
+            }
+            let style = status_bar
+                .segment_styles
+                .iter()
+                .find(|(segment, _)| segment == name)
+                .map(|&(_, style)| style)
+                .unwrap_or_default();
+            content.push(Span::styled(text, style));
+        }
+        content
+    })
+}
+
+// Draw the status bar's segments. The center and right are drawn first, so the left is kept if they overlap
+pub fn render_status_bar(frame: &mut Frame, area: Rect, status_bar: &StatusBarStruct) {
+    frame.buffer_mut().set_style(area, status_bar.style);
+    let alignments = [Alignment::Left, Alignment::Center, Alignment::Right];
+    for index in [1, 2, 0] {
+        let line = Line::from(status_bar.content[index].clone()).alignment(alignments[index]);
+        frame.render_widget(line, area);
+    }
+}
+
+// Get the branch checked out in the git repository a file is in, or the start of the commit if none is
+pub fn get_git_branch(file_path: &str) -> Option<String> {
+    let path = fs::canonicalize(file_path).ok()?;
+    for dir in path.ancestors().skip(1) {
+        let git_path = dir.join(".git");
+        // Worktrees and submodules have a .git file saying where the repository's files are
+        let git_dir = match git_path.is_file() {
+            true => {
+                let text = fs::read_to_string(&git_path).ok()?;
+                let git_dir = Path::new(text.trim().strip_prefix("gitdir:")?.trim());
+                dir.join(git_dir)
+            }
+            false if git_path.is_dir() => git_path,
+            false => continue,
+        };
+        let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+        let head = head.trim();
+        return match head.strip_prefix("ref: refs/heads/") {
+            Some(branch) => Some(branch.to_string()),
+            None => Some(head.chars().take(7).collect()),
+        };
+    }
+    None
+}
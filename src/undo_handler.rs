@@ -0,0 +1,701 @@
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Modifier, Style},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+use tui_textarea::TextArea;
+
+use crate::{
+    config_handler::get_state_dir,
+    edit_handler::jump_to,
+    file_handler::{get_content_hash, get_state_name},
+};
+
+// The largest an undo history file can get, older edits are dropped to keep it under this
+const MAX_HISTORY_BYTES: usize = 1024 * 1024;
+// Undo history files that haven't been saved to for this long are deleted
+const MAX_HISTORY_AGE: Duration = Duration::from_secs(60 * 60 * 24 * 30);
+
+// Setup the struct which holds one edit, as the lines that were replaced and the lines that replaced them
+#[derive(Clone)]
+pub struct EditStruct {
+    pub start: usize,
+    pub old_lines: Vec<String>,
+    pub new_lines: Vec<String>,
+    pub cursor_before: (usize, usize),
+    pub cursor_after: (usize, usize),
+    // Seconds since the unix epoch
+    pub time: u64,
+}
+
+// Setup the struct which holds one state of the file in the undo tree, as the edit that made it from its parent
+#[derive(Clone)]
+pub struct NodeStruct {
+    pub parent: usize,
+    pub edit: EditStruct,
+    // The child redo goes to, which is the one that was made or visited last
+    pub last_child: Option<usize>,
+}
+
+// Setup the struct which holds the undo tree for the file
+pub struct HistoryStruct {
+    // The first node is the state the history starts from and has no edit. Nodes are kept in the order they were
+    // made, so their index is also their place in time
+    pub nodes: Vec<NodeStruct>,
+    // The node for the state the file is currently in
+    pub current: usize,
+    // The lines as of the last edit, to find what the next edit changed
+    pub snapshot: Vec<String>,
+    // The cursor as of the last finished command, which is where it was before the next edit
+    pub last_cursor: (usize, usize),
+    // The lines changed since they were last taken, as (start, old line count, new line count), so marks can be
+    // moved along with the lines they are on
+    pub line_changes: Vec<(usize, usize, usize)>,
+}
+
+// Setup the struct which holds the undo tree popup, where states can be previewed before going to one
+pub struct HistoryBrowserStruct {
+    // The node the file was in when the popup was opened, which Esc goes back to
+    pub original: usize,
+    pub selected: usize,
+    // The nodes in the order they are listed, with how far each is indented. The tree doesn't change while the
+    // popup is open, so these are found once when it opens
+    rows: Vec<(usize, usize)>,
+}
+
+pub fn get_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or(0)
+}
+
+// Get the directory undo histories are saved in
+fn get_history_dir() -> PathBuf {
+    get_state_dir().join("undo")
+}
+
+fn new_root() -> NodeStruct {
+    NodeStruct {
+        parent: 0,
+        edit: EditStruct {
+            start: 0,
+            old_lines: Vec::new(),
+            new_lines: Vec::new(),
+            cursor_before: (0, 0),
+            cursor_after: (0, 0),
+            time: 0,
+        },
+        last_child: None,
+    }
+}
+
+// Load the undo history saved for a file, as long as the file hasn't changed since it was saved
+pub fn load_history(file_path: &String, lines: &[String]) -> HistoryStruct {
+    clean_histories();
+    let mut history = HistoryStruct {
+        nodes: vec![new_root()],
+        current: 0,
+        snapshot: lines.to_vec(),
+        last_cursor: (0, 0),
+        line_changes: Vec::new(),
+    };
+    let history_file = match fs::read_to_string(get_history_dir().join(get_state_name(file_path))) {
+        Ok(history_file) => history_file,
+        Err(_) => return history,
+    };
+    if let Some((nodes, current)) = parse_history(&history_file, get_content_hash(lines)) {
+        history.nodes = nodes;
+        history.current = current;
+    }
+    history
+}
+
+// Read the nodes from an undo history file, if it was saved for lines with the same hash
+fn parse_history(history_file: &str, content_hash: u64) -> Option<(Vec<NodeStruct>, usize)> {
+    let mut file_lines = history_file.split('\n');
+    let hash = file_lines.next()?.strip_prefix("hash ")?;
+    if u64::from_str_radix(hash, 16).ok()? != content_hash {
+        return None;
+    }
+    let current: usize = file_lines.next()?.strip_prefix("current ")?.parse().ok()?;
+    let mut nodes = vec![new_root()];
+    while let Some(header) = file_lines.next() {
+        let numbers: Vec<usize> = match header.strip_prefix("node ") {
+            Some(numbers) => numbers
+                .split(' ')
+                .map(|number| number.parse().ok())
+                .collect::<Option<_>>()?,
+            None => continue,
+        };
+        let [parent, last_child, start, old_count, new_count, before_row, before_col, after_row, after_col, time] =
+            numbers[..]
+        else {
+            return None;
+        };
+        let mut read_lines = |count: usize| -> Option<Vec<String>> {
+            (0..count)
+                .map(|_| Some(file_lines.next()?.get(1..)?.to_string()))
+                .collect()
+        };
+        // Parents are always made before their children
+        if parent >= nodes.len() {
+            return None;
+        }
+        nodes.push(NodeStruct {
+            parent,
+            edit: EditStruct {
+                start,
+                old_lines: read_lines(old_count)?,
+                new_lines: read_lines(new_count)?,
+                cursor_before: (before_row, before_col),
+                cursor_after: (after_row, after_col),
+                time: time as u64,
+            },
+            // 0 is the first node, which is never a child, so it is used for no child
+            last_child: match last_child {
+                0 => None,
+                last_child => Some(last_child),
+            },
+        });
+    }
+    let is_valid = current < nodes.len()
+        && nodes
+            .iter()
+            .all(|node| node.last_child.is_none_or(|child| child < nodes.len()));
+    match is_valid {
+        true => Some((nodes, current)),
+        false => None,
+    }
+}
+
+// Write a node in the format read by parse_history
+fn write_node(history_file: &mut String, node: &NodeStruct) {
+    let edit = &node.edit;
+    history_file.push_str(&format!(
+        "node {} {} {} {} {} {} {} {} {} {}\n",
+        node.parent,
+        node.last_child.unwrap_or(0),
+        edit.start,
+        edit.old_lines.len(),
+        edit.new_lines.len(),
+        edit.cursor_before.0,
+        edit.cursor_before.1,
+        edit.cursor_after.0,
+        edit.cursor_after.1,
+        edit.time
+    ));
+    for line in &edit.old_lines {
+        history_file.push_str(&format!("-{line}\n"));
+    }
+    for line in &edit.new_lines {
+        history_file.push_str(&format!("+{line}\n"));
+    }
+}
+
+// Write the nodes (after the first, which has no edit) in the format read by parse_history, for lines with a hash
+fn write_history(nodes: &[NodeStruct], current: usize, content_hash: u64) -> String {
+    let mut history_file = format!("hash {content_hash:016x}\ncurrent {current}\n");
+    for node in &nodes[1..] {
+        write_node(&mut history_file, node);
+    }
+    history_file
+}
+
+// Get the size a node takes up in a history file
+fn get_node_bytes(node: &NodeStruct) -> usize {
+    let mut node_text = String::new();
+    write_node(&mut node_text, node);
+    node_text.len()
+}
+
+// Drop every branch except the one redo and undo go along from the current state, leaving a straight line of nodes
+fn keep_current_branch(history: &mut HistoryStruct) {
+    let mut branch = vec![history.current];
+    while branch[0] != 0 {
+        branch.insert(0, history.nodes[branch[0]].parent);
+    }
+    let mut next = history.nodes[history.current].last_child;
+    while let Some(node) = next {
+        branch.push(node);
+        next = history.nodes[node].last_child;
+    }
+    history.current = branch
+        .iter()
+        .position(|&node| node == history.current)
+        .unwrap();
+    let branch_length = branch.len();
+    history.nodes = branch
+        .into_iter()
+        .enumerate()
+        .map(|(i, node)| NodeStruct {
+            parent: i.saturating_sub(1),
+            last_child: match i + 1 < branch_length {
+                true => Some(i + 1),
+                false => None,
+            },
+            ..history.nodes[node].clone()
+        })
+        .collect();
+}
+
+// Save the undo history for a file, called when the file is saved so the history matches what is on disk
+pub fn save_history(history: &mut HistoryStruct, file_path: &String) {
+    let mut total_bytes: usize = history.nodes[1..].iter().map(get_node_bytes).sum();
+    // Drop the other branches, then the oldest edits (or the newest undone ones, if everything has been undone) to
+    // keep under the size limit
+    if total_bytes > MAX_HISTORY_BYTES {
+        keep_current_branch(history);
+        total_bytes = history.nodes[1..].iter().map(get_node_bytes).sum();
+    }
+    while total_bytes > MAX_HISTORY_BYTES && history.nodes.len() > 1 {
+        let removed = match history.current {
+            0 => history.nodes.pop().unwrap(),
+            _ => {
+                // The first edit is dropped, so the state after it becomes the one the history starts from
+                let removed = history.nodes.remove(1);
+                history.current -= 1;
+                history.nodes[0].last_child = Some(1);
+                for node in history.nodes.iter_mut().skip(1) {
+                    node.parent = node.parent.saturating_sub(1);
+                    node.last_child = node.last_child.map(|child| child - 1);
+                }
+                removed
+            }
+        };
+        let node_count = history.nodes.len();
+        if let Some(last) = history.nodes.last_mut() {
+            if last.last_child == Some(node_count) {
+                last.last_child = None;
+            }
+        }
+        total_bytes -= get_node_bytes(&removed);
+    }
+    let history_file = write_history(
+        &history.nodes,
+        history.current,
+        get_content_hash(&history.snapshot),
+    );
+    let _ = fs::create_dir_all(get_history_dir());
+    let _ = fs::write(
+        get_history_dir().join(get_state_name(file_path)),
+        history_file,
+    );
+}
+
+// Delete undo histories that haven't been saved to in a long time
+fn clean_histories() {
+    let entries = match fs::read_dir(get_history_dir()) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let age = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok());
+        if matches!(age, Some(age) if age > MAX_HISTORY_AGE) {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+// Find what changed since the last snapshot, and add it as an edit. Returns true if anything changed
+pub fn record_history(history: &mut HistoryStruct, input_area: &TextArea) -> bool {
+    let lines = input_area.lines();
+    if lines == history.snapshot.as_slice() {
+        history.last_cursor = input_area.cursor();
+        return false;
+    }
+    // Trim the lines that are the same at the start and end, leaving just the lines that changed
+    let old = &history.snapshot;
+    let mut start = 0;
+    while start < old.len() && start < lines.len() && old[start] == lines[start] {
+        start += 1;
+    }
+    let mut end = 0;
+    while end < old.len() - start
+        && end < lines.len() - start
+        && old[old.len() - 1 - end] == lines[lines.len() - 1 - end]
+    {
+        end += 1;
+    }
+    let edit = EditStruct {
+        start,
+        old_lines: old[start..old.len() - end].to_vec(),
+        new_lines: lines[start..lines.len() - end].to_vec(),
+        cursor_before: history.last_cursor,
+        cursor_after: input_area.cursor(),
+        time: get_time(),
+    };
+    history
+        .line_changes
+        .push((start, edit.old_lines.len(), edit.new_lines.len()));
+    // The edit starts a new branch from the current state, keeping any states that had been undone
+    let node = history.nodes.len();
+    history.nodes.push(NodeStruct {
+        parent: history.current,
+        edit,
+        last_child: None,
+    });
+    history.nodes[history.current].last_child = Some(node);
+    history.current = node;
+    history.snapshot = lines.to_vec();
+    history.last_cursor = input_area.cursor();
+    true
+}
+
+// Take the lines changed since this was last called
+pub fn take_line_changes(history: &mut HistoryStruct) -> Vec<(usize, usize, usize)> {
+    std::mem::take(&mut history.line_changes)
+}
+
+// Replace count lines starting at start with new lines
+fn replace_lines(input_area: &mut TextArea, start: usize, count: usize, new_lines: &[String]) {
+    let line_count = input_area.lines().len();
+    let line_length = |input_area: &TextArea, row: usize| input_area.lines()[row].chars().count();
+    input_area.cancel_selection();
+    match start + count < line_count {
+        true => {
+            jump_to(input_area, start, 0);
+            input_area.start_selection();
+            jump_to(input_area, start + count, 0);
+            input_area.delete_str(0);
+            input_area.cancel_selection();
+            if !new_lines.is_empty() {
+                input_area.insert_str(format!("{}\n", new_lines.join("\n")));
+            }
+        }
+        // The lines run to the end of the file, so take the newline before them instead of the one after
+        false if start > 0 => {
+            jump_to(input_area, start - 1, line_length(input_area, start - 1));
+            input_area.start_selection();
+            jump_to(
+                input_area,
+                line_count - 1,
+                line_length(input_area, line_count - 1),
+            );
+            input_area.delete_str(0);
+            input_area.cancel_selection();
+            if !new_lines.is_empty() {
+                input_area.insert_str(format!("\n{}", new_lines.join("\n")));
+            }
+        }
+        false => {
+            input_area.select_all();
+            input_area.delete_str(0);
+            input_area.cancel_selection();
+            input_area.insert_str(new_lines.join("\n"));
+        }
+    }
+}
+
+// Undo the edit that made the current state, returns true if there was one
+pub fn undo(history: &mut HistoryStruct, input_area: &mut TextArea) -> bool {
+    if history.current == 0 {
+        return false;
+    }
+    let node = &history.nodes[history.current];
+    let edit = &node.edit;
+    replace_lines(
+        input_area,
+        edit.start,
+        edit.new_lines.len(),
+        &edit.old_lines,
+    );
+    jump_to(input_area, edit.cursor_before.0, edit.cursor_before.1);
+    history
+        .line_changes
+        .push((edit.start, edit.new_lines.len(), edit.old_lines.len()));
+    // Redo comes back down the branch that was undone
+    let parent = node.parent;
+    history.nodes[parent].last_child = Some(history.current);
+    history.current = parent;
+    history.snapshot = input_area.lines().to_vec();
+    history.last_cursor = input_area.cursor();
+    true
+}
+
+// Redo the edit to the last made or visited child of the current state, returns true if there was one
+pub fn redo(history: &mut HistoryStruct, input_area: &mut TextArea) -> bool {
+    let child = match history.nodes[history.current].last_child {
+        Some(child) => child,
+        None => return false,
+    };
+    let edit = &history.nodes[child].edit;
+    replace_lines(
+        input_area,
+        edit.start,
+        edit.old_lines.len(),
+        &edit.new_lines,
+    );
+    jump_to(input_area, edit.cursor_after.0, edit.cursor_after.1);
+    history
+        .line_changes
+        .push((edit.start, edit.old_lines.len(), edit.new_lines.len()));
+    history.current = child;
+    history.snapshot = input_area.lines().to_vec();
+    history.last_cursor = input_area.cursor();
+    true
+}
+
+// Go to any state in the tree, by undoing back to where its branch splits off and redoing down it
+// Returns true if the state changed
+pub fn go_to_state(history: &mut HistoryStruct, input_area: &mut TextArea, target: usize) -> bool {
+    if target >= history.nodes.len() || target == history.current {
+        return false;
+    }
+    let mut branch = vec![target];
+    while *branch.last().unwrap() != 0 {
+        branch.push(history.nodes[*branch.last().unwrap()].parent);
+    }
+    while !branch.contains(&history.current) {
+        undo(history, input_area);
+    }
+    let split = branch
+        .iter()
+        .position(|&node| node == history.current)
+        .unwrap();
+    for &node in branch[..split].iter().rev() {
+        history.nodes[history.current].last_child = Some(node);
+        redo(history, input_area);
+    }
+    true
+}
+
+// Get the state made count steps before (or after, if is_newer) the current one in time, which can be on another branch
+pub fn get_state_in_time(history: &HistoryStruct, count: usize, is_newer: bool) -> usize {
+    match is_newer {
+        true => history
+            .current
+            .saturating_add(count)
+            .min(history.nodes.len() - 1),
+        false => history.current.saturating_sub(count),
+    }
+}
+
+// Get the state the file was in a number of minutes ago (the last state made at or before then)
+pub fn get_state_minutes_ago(history: &HistoryStruct, minutes: &str) -> Option<usize> {
+    let minutes: u64 = minutes.trim().parse().ok()?;
+    let time = get_time().saturating_sub(minutes.saturating_mul(60));
+    Some(
+        history
+            .nodes
+            .iter()
+            .rposition(|node| node.edit.time <= time)
+            .unwrap_or(0),
+    )
+}
+
+// Get the nodes in the order they are listed in the undo tree popup, with how far each is indented
+// Children are listed after their parent (oldest first), and each newer branch is indented one more step
+fn get_tree_rows(history: &HistoryStruct) -> Vec<(usize, usize)> {
+    // Nodes come after their parent, so going through them in order lists each node's children oldest first
+    let mut children = vec![Vec::new(); history.nodes.len()];
+    for (node, node_struct) in history.nodes.iter().enumerate().skip(1) {
+        children[node_struct.parent].push(node);
+    }
+    let mut rows = Vec::new();
+    let mut stack = vec![(0, 0)];
+    while let Some((node, indent)) = stack.pop() {
+        rows.push((node, indent));
+        // Pushed newest first, so the oldest child comes off the stack first
+        for (i, &child) in children[node].iter().enumerate().rev() {
+            stack.push((child, indent + i));
+        }
+    }
+    rows
+}
+
+// Describe how long ago a time was
+fn format_age(time: u64, now: u64) -> String {
+    let seconds = now.saturating_sub(time);
+    match seconds {
+        0..=59 => format!("{seconds}s ago"),
+        60..=3599 => format!("{}m ago", seconds / 60),
+        3600..=86399 => format!("{}h ago", seconds / 3600),
+        _ => format!("{}d ago", seconds / 86400),
+    }
+}
+
+pub fn open_history_browser(history: &HistoryStruct) -> HistoryBrowserStruct {
+    HistoryBrowserStruct {
+        original: history.current,
+        selected: history.current,
+        rows: get_tree_rows(history),
+    }
+}
+
+// Move the selection in the undo tree popup up or down a row, and preview the selected state
+pub fn move_history_browser(
+    browser: &mut HistoryBrowserStruct,
+    history: &mut HistoryStruct,
+    input_area: &mut TextArea,
+    is_down: bool,
+) -> bool {
+    let rows = &browser.rows;
+    let row = rows
+        .iter()
+        .position(|&(node, _)| node == browser.selected)
+        .unwrap_or(0);
+    let row = match is_down {
+        true => (row + 1).min(rows.len() - 1),
+        false => row.saturating_sub(1),
+    };
+    browser.selected = rows[row].0;
+    go_to_state(history, input_area, browser.selected)
+}
+
+// Draw the undo tree popup over the editor, with a row for each state
+pub fn render_history_browser(
+    frame: &mut Frame,
+    area: Rect,
+    history: &HistoryStruct,
+    browser: &HistoryBrowserStruct,
+) {
+    let now = get_time();
+    let rows = &browser.rows;
+    let items: Vec<ListItem> = rows
+        .iter()
+        .map(|&(node, indent)| {
+            let marker = match node == history.current {
+                true => "*",
+                false => "o",
+            };
+            let description = match node {
+                0 => "start of history".to_string(),
+                _ => {
+                    let edit = &history.nodes[node].edit;
+                    format!(
+                        "{age} | line {line}: -{removed} +{added} {preview}",
+                        age = format_age(edit.time, now),
+                        line = edit.start + 1,
+                        removed = edit.old_lines.len(),
+                        added = edit.new_lines.len(),
+                        preview = edit
+                            .new_lines
+                            .first()
+                            .or(edit.old_lines.first())
+                            .map(|line| line.trim())
+                            .unwrap_or("")
+                    )
+                }
+            };
+            ListItem::new(format!(
+                "{branches}{marker} {node} | {description}",
+                branches = "| ".repeat(indent)
+            ))
+        })
+        .collect();
+    let selected = rows.iter().position(|&(node, _)| node == browser.selected);
+    let [_, popup_area, _] = Layout::vertical([
+        Constraint::Percentage(15),
+        Constraint::Percentage(70),
+        Constraint::Percentage(15),
+    ])
+    .areas(area);
+    let [_, popup_area, _] = Layout::horizontal([
+        Constraint::Percentage(15),
+        Constraint::Percentage(70),
+        Constraint::Percentage(15),
+    ])
+    .areas(popup_area);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title("Undo Tree (j/k: preview, Enter: go to, Esc: cancel)")
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    frame.render_widget(Clear, popup_area);
+    frame.render_stateful_widget(
+        list,
+        popup_area,
+        &mut ListState::default().with_selected(selected),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_history, write_history, EditStruct, NodeStruct};
+
+    fn new_node(
+        parent: usize,
+        last_child: Option<usize>,
+        old: &[&str],
+        new: &[&str],
+    ) -> NodeStruct {
+        NodeStruct {
+            parent,
+            edit: EditStruct {
+                start: 2,
+                old_lines: old.iter().map(|line| line.to_string()).collect(),
+                new_lines: new.iter().map(|line| line.to_string()).collect(),
+                cursor_before: (2, 4),
+                cursor_after: (3, 0),
+                time: 1_700_000_000,
+            },
+            last_child,
+        }
+    }
+
+    // A tree with a branch: node 1 has the children 2 and 3, and redo goes to 3
+    fn new_tree() -> Vec<NodeStruct> {
+        let root = new_node(0, Some(1), &[], &[]);
+        vec![
+            root,
+            new_node(0, Some(3), &["old"], &["new", ""]),
+            new_node(1, None, &["", "+plus", "-minus"], &[]),
+            new_node(1, None, &[], &["ünïcode", "node 1 2 3"]),
+        ]
+    }
+
+    #[test]
+    fn history_round_trips() {
+        let history_file = write_history(&new_tree(), 2, 0xabc);
+        let (nodes, current) = parse_history(&history_file, 0xabc).unwrap();
+        assert_eq!(current, 2);
+        assert_eq!(nodes.len(), 4);
+        assert_eq!(nodes[2].parent, 1);
+        assert_eq!(nodes[1].last_child, Some(3));
+        assert_eq!(nodes[2].last_child, None);
+        assert_eq!(nodes[2].edit.old_lines, ["", "+plus", "-minus"]);
+        assert_eq!(nodes[3].edit.new_lines, ["ünïcode", "node 1 2 3"]);
+        assert_eq!(nodes[3].edit.cursor_before, (2, 4));
+        assert_eq!(nodes[3].edit.time, 1_700_000_000);
+        assert_eq!(write_history(&nodes, current, 0xabc), history_file);
+    }
+
+    #[test]
+    fn history_for_other_lines_is_not_read() {
+        let history_file = write_history(&new_tree(), 2, 0xabc);
+        assert!(parse_history(&history_file, 0xabd).is_none());
+    }
+
+    #[test]
+    fn bad_history_is_not_read() {
+        let node = "node 0 0 0 1 1 0 0 0 0 5\n-a\n+b\n";
+        let read = |history_file: String| parse_history(&history_file, 1);
+        assert!(read(format!("hash 1\ncurrent 1\n{node}")).is_some());
+        assert!(read(String::new()).is_none());
+        assert!(read(format!("hash x\ncurrent 1\n{node}")).is_none());
+        assert!(read(format!("hash 1\n{node}")).is_none());
+        // The current node, a parent or a last child that isn't in the history
+        assert!(read(format!("hash 1\ncurrent 2\n{node}")).is_none());
+        assert!(read("hash 1\ncurrent 1\nnode 1 0 0 0 0 0 0 0 0 5\n".to_string()).is_none());
+        assert!(read("hash 1\ncurrent 1\nnode 0 2 0 0 0 0 0 0 0 5\n".to_string()).is_none());
+        // Missing or bad numbers, and fewer lines than the node says it has
+        assert!(read("hash 1\ncurrent 1\nnode 0 0 0 0 0 0 0 0 5\n".to_string()).is_none());
+        assert!(read("hash 1\ncurrent 1\nnode 0 0 0 0 0 0 0 0 0 -5\n".to_string()).is_none());
+        assert!(read("hash 1\ncurrent 1\nnode 0 0 0 2 0 0 0 0 0 5\n-a".to_string()).is_none());
+    }
+}
@@ -0,0 +1,330 @@
+use configparser::ini::Ini;
+use ratatui::{layout::Rect, text::Span, widgets::Widget, Frame};
+use tui_textarea::TextArea;
+
+use crate::{
+    bracket_handler::get_match_style, config_handler::get_config_value, edit_handler::jump_to,
+    language_handler::LanguageStruct,
+};
+
+// Shown in the gutter next to screen lines that carry on a line from the one above
+const WRAP_MARKER: &str = "↪";
+
+// Setup the struct which holds the soft wrap settings, and where the wrapped text is scrolled to
+pub struct WrapStruct {
+    pub is_on: bool,
+    // The column lines wrap at, if they should wrap before the edge of the window
+    pub column: Option<usize>,
+    // Whether j/k (and the up/down arrow keys) move by screen lines rather than by lines in the file
+    pub is_screen_movement: bool,
+    // The first row shown, and how many of its screen lines are scrolled past
+    top: (usize, usize),
+    // The width lines were wrapped to when last drawn, used to move by screen lines
+    width: usize,
+}
+
+pub fn new_wrap() -> WrapStruct {
+    WrapStruct {
+        is_on: false,
+        column: None,
+        is_screen_movement: true,
+        top: (0, 0),
+        width: 0,
+    }
+}
+
+// Get the wrap settings for a file from the config (or its language's defaults)
+pub fn apply_wrap_config(
+    wrap: &mut WrapStruct,
+    config: &Ini,
+    file_path: &String,
+    language: &LanguageStruct,
+) {
+    wrap.is_on = get_config_value(config, "wrap", file_path, language).as_deref() == Some("true");
+    wrap.column = get_config_value(config, "wrapcolumn", file_path, language)
+        .and_then(|column| column.parse().ok())
+        .filter(|&column| column > 0);
+    wrap.is_screen_movement =
+        get_config_value(config, "wrapmovement", file_path, language).as_deref() != Some("line");
+}
+
+// Get how many columns a character takes up on screen, when it starts x columns into a screen line
+pub fn get_char_width(c: char, x: usize, tab_length: u8) -> usize {
+    match c {
+        '\t' => {
+            let tab_length = tab_length.max(1) as usize;
+            tab_length - x % tab_length
+        }
+        _ => Span::raw(c.to_string()).width(),
+    }
+}
+
+// Get how many columns the characters from start to end of a line take up, when start begins a screen line
+fn get_width(chars: &[char], start: usize, end: usize, tab_length: u8) -> usize {
+    let mut x = 0;
+    for &c in &chars[start..end] {
+        x += get_char_width(c, x, tab_length);
+    }
+    x
+}
+
+// Get the character that is at (or covers) a column of the screen line starting at start, or end if the screen line
+// isn't that wide
+fn get_col_at(
+    chars: &[char],
+    start: usize,
+    end: usize,
+    screen_col: usize,
+    tab_length: u8,
+) -> usize {
+    let mut x = 0;
+    for (col, &c) in chars.iter().enumerate().take(end).skip(start) {
+        x += get_char_width(c, x, tab_length);
+        if x > screen_col {
+            return col;
+        }
+    }
+    end
+}
+
+// Split a line into screen lines no wider than width, as the index of the character each one starts at
+// Lines are split after the last space that fits where there is one, so words are kept together
+pub fn get_wrap_starts(line: &str, width: usize, tab_length: u8) -> Vec<usize> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut starts = vec![0];
+    let mut start = 0;
+    let mut x = 0;
+    // Where the screen line can be split without splitting a word
+    let mut word_break = None;
+    let mut i = 0;
+    while i < chars.len() {
+        let char_width = get_char_width(chars[i], x, tab_length);
+        if x + char_width > width && i > start {
+            start = word_break
+                .filter(|&word_break| word_break > start)
+                .unwrap_or(i);
+            starts.push(start);
+            x = 0;
+            word_break = None;
+            i = start;
+            continue;
+        }
+        x += char_width;
+        if chars[i].is_whitespace() {
+            word_break = Some(i + 1);
+        }
+        i += 1;
+    }
+    starts
+}
+
+// Get where a row's screen lines start. The cursor can be after the last character, which needs a screen line of
+// its own when the last one is full
+fn get_row_starts(input_area: &TextArea, row: usize, width: usize) -> Vec<usize> {
+    let line = &input_area.lines()[row];
+    let tab_length = input_area.tab_length();
+    let mut starts = get_wrap_starts(line, width, tab_length);
+    let chars: Vec<char> = line.chars().collect();
+    let last = *starts.last().unwrap();
+    if input_area.cursor() == (row, chars.len())
+        && !chars.is_empty()
+        && get_width(&chars, last, chars.len(), tab_length) >= width
+    {
+        starts.push(chars.len());
+    }
+    starts
+}
+
+// Get which of a row's screen lines a column is on
+fn get_segment(starts: &[usize], col: usize) -> usize {
+    starts.iter().rposition(|&start| start <= col).unwrap_or(0)
+}
+
+// Scroll so the cursor's screen line is on screen, moving as little as possible
+fn scroll_to_cursor(input_area: &TextArea, wrap: &mut WrapStruct, height: usize) {
+    let (cursor_row, cursor_col) = input_area.cursor();
+    let cursor = (
+        cursor_row,
+        get_segment(
+            &get_row_starts(input_area, cursor_row, wrap.width),
+            cursor_col,
+        ),
+    );
+    // The text may have got shorter since the last draw
+    if wrap.top.0 >= input_area.lines().len() {
+        wrap.top = (input_area.lines().len() - 1, 0);
+    }
+    wrap.top.1 = wrap
+        .top
+        .1
+        .min(get_row_starts(input_area, wrap.top.0, wrap.width).len() - 1);
+    if cursor < wrap.top {
+        wrap.top = cursor;
+        return;
+    }
+    // Every row takes at least one screen line, so rows further up than the height can't be on screen
+    if cursor_row - wrap.top.0 >= height {
+        wrap.top = (cursor_row + 1 - height.max(1), 0);
+    }
+    let mut screen_lines = cursor.1 + 1;
+    for row in wrap.top.0..cursor_row {
+        screen_lines += get_row_starts(input_area, row, wrap.width).len();
+    }
+    screen_lines -= wrap.top.1;
+    while screen_lines > height.max(1) {
+        let top_segments = get_row_starts(input_area, wrap.top.0, wrap.width).len();
+        wrap.top = match wrap.top.1 + 1 < top_segments {
+            true => (wrap.top.0, wrap.top.1 + 1),
+            false => (wrap.top.0 + 1, 0),
+        };
+        screen_lines -= 1;
+    }
+}
+
+// Draw the input_area with its lines wrapped, in place of rendering it (which would let long lines go off screen)
+// highlight is a character to draw with the matching bracket style
+pub fn render_wrapped(
+    frame: &mut Frame,
+    area: Rect,
+    input_area: &mut TextArea,
+    wrap: &mut WrapStruct,
+    highlight: Option<(usize, usize)>,
+) {
+    let selection_style = input_area.selection_style();
+    let inner = match input_area.block() {
+        Some(block) => {
+            block.render(area, frame.buffer_mut());
+            block.inner(area)
+        }
+        None => area,
+    };
+    let inner = inner.intersection(frame.area());
+    if inner.is_empty() {
+        return;
+    }
+    let line_number_style = input_area.line_number_style();
+    let number_width = input_area.lines().len().to_string().len();
+    // The gutter holds the line numbers (if they are shown) and the markers on wrapped screen lines
+    let gutter = match line_number_style {
+        Some(_) => number_width + 2,
+        None => 2,
+    };
+    let text_width = (inner.width as usize).saturating_sub(gutter).max(1);
+    wrap.width = wrap
+        .column
+        .map_or(text_width, |column| column.min(text_width));
+    scroll_to_cursor(input_area, wrap, inner.height as usize);
+
+    let tab_length = input_area.tab_length();
+    let cursor = input_area.cursor();
+    let selection = input_area.selection_range();
+    let buffer = frame.buffer_mut();
+    buffer.set_style(inner, input_area.style());
+    let (mut row, mut segment) = wrap.top;
+    for y in inner.top()..inner.bottom() {
+        if row >= input_area.lines().len() {
+            break;
+        }
+        let chars: Vec<char> = input_area.lines()[row].chars().collect();
+        let starts = get_row_starts(input_area, row, wrap.width);
+        let start = starts[segment];
+        let end = starts.get(segment + 1).copied().unwrap_or(chars.len());
+        let gutter_text = match (segment, line_number_style) {
+            (0, Some(_)) => format!(" {:>number_width$} ", row + 1),
+            (0, None) => String::new(),
+            _ => format!("{WRAP_MARKER:>0$} ", gutter - 1),
+        };
+        buffer.set_string(
+            inner.x,
+            y,
+            gutter_text,
+            line_number_style.unwrap_or_default(),
+        );
+        let mut x = 0;
+        for col in start..=end {
+            // The end of the last screen line only has something to draw when the cursor is there
+            let is_line_end = col == chars.len();
+            if col == end && !(is_line_end && cursor == (row, col)) {
+                break;
+            }
+            let char_width = match is_line_end {
+                true => 1,
+                false => get_char_width(chars[col], x, tab_length),
+            };
+            let symbol = match chars.get(col) {
+                Some('\t') | None => " ".repeat(char_width),
+                Some(c) => c.to_string(),
+            };
+            let mut style = input_area.style();
+            if row == cursor.0 && !is_line_end {
+                style = style.patch(input_area.cursor_line_style());
+            }
+            if let Some((selection_start, selection_end)) = selection {
+                if selection_start <= (row, col) && (row, col) < selection_end {
+                    style = style.patch(selection_style);
+                }
+            }
+            if highlight == Some((row, col)) {
+                style = style.patch(get_match_style());
+            }
+            if cursor == (row, col) {
+                style = style.patch(input_area.cursor_style());
+            }
+            let cell_x = inner.x as usize + gutter + x;
+            if cell_x + char_width > inner.right() as usize {
+                break;
+            }
+            buffer.set_stringn(cell_x as u16, y, symbol, char_width, style);
+            x += char_width;
+        }
+        match segment + 1 < starts.len() {
+            true => segment += 1,
+            false => {
+                row += 1;
+                segment = 0;
+            }
+        }
+    }
+}
+
+// Move the cursor count screen lines down or up, keeping it in the same column on screen. Returns false (without
+// moving it) when lines aren't wrapped or j/k move by lines in the file
+pub fn move_screen_lines(
+    input_area: &mut TextArea,
+    wrap: &WrapStruct,
+    count: usize,
+    is_down: bool,
+) -> bool {
+    if !wrap.is_on || !wrap.is_screen_movement || wrap.width == 0 {
+        return false;
+    }
+    let tab_length = input_area.tab_length();
+    for _ in 0..count.max(1) {
+        let (row, col) = input_area.cursor();
+        let starts = get_row_starts(input_area, row, wrap.width);
+        let segment = get_segment(&starts, col);
+        let chars: Vec<char> = input_area.lines()[row].chars().collect();
+        let screen_col = get_width(&chars, starts[segment], col, tab_length);
+        let (row, segment) = match is_down {
+            true if segment + 1 < starts.len() => (row, segment + 1),
+            true if row + 1 < input_area.lines().len() => (row + 1, 0),
+            false if segment > 0 => (row, segment - 1),
+            false if row > 0 => (
+                row - 1,
+                get_row_starts(input_area, row - 1, wrap.width).len() - 1,
+            ),
+            _ => break,
+        };
+        let starts = get_row_starts(input_area, row, wrap.width);
+        let chars: Vec<char> = input_area.lines()[row].chars().collect();
+        let start = starts[segment];
+        let end = starts.get(segment + 1).copied().unwrap_or(chars.len());
+        let mut col = get_col_at(&chars, start, end, screen_col, tab_length);
+        // The end of a screen line that isn't the last one is the start of the next one
+        if col == end && end < chars.len() && end > start {
+            col = end - 1;
+        }
+        jump_to(input_area, row, col);
+    }
+    true
+}